@@ -0,0 +1,117 @@
+use clap::Parser;
+use color_eyre::{eyre::Context, Result};
+use owo_colors::OwoColorize;
+
+use fl_plugin_sorter::{
+    config::Config,
+    coverage::{coverage, TypeCoverage},
+};
+
+use super::{OutputFormat, RunnableCommand};
+
+/// Compares the installed plugins against the plugins in plugin groups
+#[derive(Debug, Parser)]
+pub struct CoverageSubcommand {
+    /// Format used to display the report
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+impl RunnableCommand for CoverageSubcommand {
+    fn run(self, config: &Config) -> Result<()> {
+        let report = coverage(config)?;
+        if let OutputFormat::Json = self.format {
+            let json = serde_json::to_string_pretty(&report)
+                .wrap_err("failed to serialize coverage report")?;
+            println!("{json}");
+            return Ok(());
+        }
+
+        self.display_table(&report);
+        for type_coverage in &report {
+            self.display_lists(type_coverage);
+        }
+
+        Ok(())
+    }
+}
+
+impl CoverageSubcommand {
+    fn display_table(&self, report: &[TypeCoverage]) {
+        println!(
+            "{}",
+            format!(
+                "{:<10} {:>9} {:>7} {:>9} {:>13} {:>18}",
+                "Type", "Installed", "Grouped", "Ungrouped", "Not installed", "In multiple groups"
+            )
+            .bold()
+        );
+
+        for type_coverage in report {
+            println!(
+                "{:<10} {:>9} {:>7} {:>9} {:>13} {:>18}",
+                type_coverage.group_type,
+                type_coverage.installed,
+                type_coverage.grouped,
+                type_coverage.ungrouped.len(),
+                type_coverage.not_installed.len(),
+                type_coverage.duplicates.len()
+            );
+        }
+    }
+
+    fn display_lists(&self, type_coverage: &TypeCoverage) {
+        let group_type = &type_coverage.group_type;
+        if !type_coverage.ungrouped.is_empty() {
+            println!(
+                "\n{}",
+                format!("Ungrouped {group_type} plugins")
+                    .blue()
+                    .bold()
+                    .underline()
+            );
+            for plugin in &type_coverage.ungrouped {
+                println!("  {}", plugin.green());
+            }
+        }
+
+        if !type_coverage.not_installed.is_empty() {
+            println!(
+                "\n{}",
+                format!("Grouped {group_type} plugins that aren't installed")
+                    .blue()
+                    .bold()
+                    .underline()
+            );
+            for missing in &type_coverage.not_installed {
+                println!(
+                    "  {} ({})",
+                    missing.plugin.yellow(),
+                    missing.group.display().cyan()
+                );
+            }
+        }
+
+        if !type_coverage.duplicates.is_empty() {
+            println!(
+                "\n{}",
+                format!("Duplicate {group_type} plugins")
+                    .blue()
+                    .bold()
+                    .underline()
+            );
+            for duplicate in &type_coverage.duplicates {
+                let groups: Vec<String> = duplicate
+                    .groups
+                    .iter()
+                    .map(|group| group.display().to_string())
+                    .collect();
+                println!(
+                    "  {} ({})",
+                    duplicate.plugin.yellow(),
+                    groups.join(", ").cyan()
+                );
+            }
+        }
+    }
+}
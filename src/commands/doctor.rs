@@ -0,0 +1,102 @@
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use owo_colors::OwoColorize;
+
+use fl_plugin_sorter::{
+    config::Config,
+    doctor::{diagnose, Report, Severity},
+    state::SortState,
+};
+
+use super::RunnableCommand;
+
+/// Checks the plugin groups, plugin database and sorted plugins for problems
+#[derive(Debug, Parser)]
+pub struct DoctorSubcommand {
+    /// Repair the problems that can be repaired safely, such as outdated sorted plugins
+    #[arg(long, action)]
+    fix: bool,
+}
+
+impl RunnableCommand for DoctorSubcommand {
+    fn run(self, config: &Config) -> Result<()> {
+        let report = diagnose(config)?;
+        self.display_report(&report);
+
+        if self.fix {
+            let plan = report.fix_plan();
+            if !plan.actions.is_empty() {
+                let mut state = SortState::from_file(&config.state_path)?;
+                let executed = plan.execute(&mut state);
+                state
+                    .save(&config.state_path)
+                    .wrap_err("failed to save sort state")?;
+                executed?;
+
+                let fixed_count = plan.actions.len();
+                println!(
+                    "{} {} {}",
+                    "Fixed".green(),
+                    fixed_count.cyan().bold(),
+                    format!("problem{}", if fixed_count == 1 { "" } else { "s" }).green()
+                );
+            }
+        }
+
+        let error_count = report.count(Severity::Error);
+        if error_count > 0 {
+            return Err(eyre!(
+                "found {error_count} error{}",
+                if error_count == 1 { "" } else { "s" }
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl DoctorSubcommand {
+    fn display_report(&self, report: &Report) {
+        if report.problems.is_empty() {
+            println!("{}", "Found no problems.".green());
+            return;
+        }
+
+        let mut category = None;
+        for problem in &report.problems {
+            if category != Some(problem.category) {
+                category = Some(problem.category);
+                println!("{}", problem.category.name().blue().bold().underline());
+            }
+
+            let severity = format!("{:<8}", problem.severity.name());
+            let severity = match problem.severity {
+                Severity::Info => severity.cyan().to_string(),
+                Severity::Warning => severity.yellow().to_string(),
+                Severity::Error => severity.red().bold().to_string(),
+            };
+
+            let fixable = if problem.fix.is_some() && !self.fix {
+                " (fixable with --fix)".dimmed().to_string()
+            } else {
+                String::new()
+            };
+
+            println!("  {severity} {}{fixable}", problem.message);
+        }
+
+        let error_count = report.count(Severity::Error);
+        let warning_count = report.count(Severity::Warning);
+        println!(
+            "\n{} {} {} {} {}",
+            "Found".green(),
+            error_count.red().bold(),
+            format!("error{} and", if error_count == 1 { "" } else { "s" }).green(),
+            warning_count.yellow().bold(),
+            format!("warning{}", if warning_count == 1 { "" } else { "s" }).green()
+        );
+    }
+}
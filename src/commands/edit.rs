@@ -0,0 +1,126 @@
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use dialoguer::{theme::ColorfulTheme, MultiSelect};
+use owo_colors::OwoColorize;
+
+use fl_plugin_sorter::config::{Config, PluginGroup};
+
+use super::{select_group, RunnableCommand};
+
+/// Adds and removes plugins of a plugin group by selecting them from the installed plugins
+#[derive(Debug, Parser)]
+pub struct EditSubcommand {}
+
+impl RunnableCommand for EditSubcommand {
+    fn run(self, config: &Config) -> Result<()> {
+        let Some((group_data, group)) = select_group(config)? else {
+            return Ok(());
+        };
+
+        let installed_names = config
+            .plugin_database()?
+            .get_group(&group_data.group_type)
+            .installed
+            .plugin_names()?;
+
+        let Some(plugins) = self.select_plugins(group, &installed_names)? else {
+            return Ok(());
+        };
+
+        if plugins == group.plugins {
+            println!(
+                "{}{}{}",
+                "No changes were made to '".green(),
+                group.name.cyan().bold(),
+                "'".green()
+            );
+            return Ok(());
+        }
+
+        let file_name = group
+            .file_path
+            .file_stem()
+            .ok_or_else(|| eyre!("'{}' wasn't loaded from a file", group.name))?
+            .to_string_lossy();
+
+        let mut edited = group.clone();
+        edited.plugins = plugins;
+        group_data
+            .save_group(&file_name, &edited)
+            .wrap_err_with(|| eyre!("failed to save '{}'", group.name))?;
+
+        println!(
+            "{} {}",
+            "Saved".green(),
+            group.file_path.display().cyan().bold()
+        );
+        Ok(())
+    }
+}
+
+impl EditSubcommand {
+    /// Prompts the user to select the plugins of the group from the installed plugins,
+    /// returning `None` if the prompt was cancelled.
+    ///
+    /// Plugins in the group that aren't installed are listed first, so they aren't
+    /// removed from the group unless they are unchecked.
+    fn select_plugins(
+        &self,
+        group: &PluginGroup,
+        installed_names: &[String],
+    ) -> Result<Option<Vec<String>>> {
+        let mut plugins: Vec<&String> = group
+            .plugins
+            .iter()
+            .filter(|plugin| installed_names.binary_search(plugin).is_err())
+            .collect();
+        let not_installed_count = plugins.len();
+        plugins.extend(installed_names);
+
+        let items: Vec<(String, bool)> = plugins
+            .iter()
+            .enumerate()
+            .map(|(i, plugin)| {
+                let name = if i < not_installed_count {
+                    format!("{plugin} (not installed)")
+                } else {
+                    plugin.to_string()
+                };
+                (name, group.plugins.contains(plugin))
+            })
+            .collect();
+
+        let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "Select the plugins of '{}', press space to toggle and enter to save",
+                group.name
+            ))
+            .items_checked(&items)
+            .max_length(15)
+            .interact_opt()?;
+
+        let Some(selection) = selection else {
+            return Ok(None);
+        };
+
+        let selected: Vec<&String> = selection.into_iter().map(|i| plugins[i]).collect();
+
+        // Keep the order of plugins that were already in the group, adding new plugins after them
+        let mut edited: Vec<String> = group
+            .plugins
+            .iter()
+            .filter(|plugin| selected.contains(plugin))
+            .cloned()
+            .collect();
+        for plugin in selected {
+            if !edited.contains(plugin) {
+                edited.push(plugin.to_owned());
+            }
+        }
+
+        Ok(Some(edited))
+    }
+}
@@ -0,0 +1,122 @@
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use owo_colors::OwoColorize;
+
+use fl_plugin_sorter::{
+    config::{Config, PluginGroup},
+    plan::MAX_SUGGESTIONS,
+    suggest::suggest,
+};
+
+use super::RunnableCommand;
+
+/// Replaces plugins that aren't installed in plugin groups with similar installed plugins
+#[derive(Debug, Parser)]
+pub struct FixSubcommand {}
+
+impl RunnableCommand for FixSubcommand {
+    fn run(self, config: &Config) -> Result<()> {
+        let plugin_database = config.plugin_database()?;
+        let mut fixed_count = 0;
+        for group_data in [&config.effects, &config.generators] {
+            let installed_names = plugin_database
+                .get_group(&group_data.group_type)
+                .installed
+                .plugin_names()?;
+
+            for group in &group_data.groups {
+                let Some(fixed) = self.fix_group(group, &installed_names)? else {
+                    continue;
+                };
+
+                fixed
+                    .save(&group.file_path)
+                    .wrap_err_with(|| eyre!("failed to save '{}'", group.name))?;
+                println!(
+                    "{} {}",
+                    "Saved".green(),
+                    group.file_path.display().cyan().bold()
+                );
+                fixed_count += 1;
+            }
+        }
+
+        if fixed_count == 0 {
+            println!("{}", "Found no plugin groups to fix.".green());
+        }
+
+        Ok(())
+    }
+}
+
+impl FixSubcommand {
+    /// Prompts the user to replace every plugin of the group that isn't installed with
+    /// a similar installed plugin, returning the fixed group if anything was replaced
+    fn fix_group(
+        &self,
+        group: &PluginGroup,
+        installed_names: &[String],
+    ) -> Result<Option<PluginGroup>> {
+        let mut plugins = Vec::with_capacity(group.plugins.len());
+        let mut changed = false;
+        for plugin in &group.plugins {
+            if installed_names.binary_search(plugin).is_ok() {
+                plugins.push(plugin.to_owned());
+                continue;
+            }
+
+            let suggestions = suggest(plugin, installed_names, MAX_SUGGESTIONS);
+            if suggestions.is_empty() {
+                println!(
+                    "{}{}{}{}{}",
+                    "No installed plugins are similar to '".yellow(),
+                    plugin.blue().bold(),
+                    "' in '".yellow(),
+                    group.name.blue().bold(),
+                    "'".yellow()
+                );
+                plugins.push(plugin.to_owned());
+                continue;
+            }
+
+            let keep = format!("Keep '{plugin}'");
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "'{plugin}' in '{}' is not installed, replace it with",
+                    group.name
+                ))
+                .items(&suggestions)
+                .item(&keep)
+                .default(0)
+                .interact_opt()?;
+
+            match selection.and_then(|i| suggestions.get(i)) {
+                Some(suggestion) => {
+                    // The replacement may already be in the group
+                    if !group
+                        .plugins
+                        .iter()
+                        .chain(&plugins)
+                        .any(|p| p == suggestion)
+                    {
+                        plugins.push(suggestion.to_string());
+                    }
+                    changed = true;
+                }
+                None => plugins.push(plugin.to_owned()),
+            }
+        }
+
+        if !changed {
+            return Ok(None);
+        }
+
+        let mut fixed = group.clone();
+        fixed.plugins = plugins;
+        Ok(Some(fixed))
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashSet},
     fs,
     path::{Path, PathBuf},
 };
@@ -9,21 +9,54 @@ use color_eyre::{
     eyre::{eyre, Context},
     Result,
 };
-use dialoguer::{theme::ColorfulTheme, MultiSelect};
+use dialoguer::{theme::ColorfulTheme, Confirm, MultiSelect};
 use owo_colors::OwoColorize;
 
-use crate::{
-    config::{Config, PluginGroup, PluginGroupType},
-    plugin::is_path_vst,
+use fl_plugin_sorter::{
+    config::{Config, PluginDatabaseGroup, PluginGroup, PluginGroupType},
+    fst::FstFile,
+    matcher::PluginMatcher,
+    plugin::{is_path_vst, PluginPathIndex},
+    validate::{sanitize_name, validate_name},
+    vendor::group_by_vendor,
 };
 
 use super::RunnableCommand;
 
-/// Generates a plugin group from a folder containing VST files
+/// Generates a plugin group from a folder containing VST files, or from the plugin database
 #[derive(Debug, Parser)]
 pub struct GenerateSubcommand {
     /// Path to a folder containing VST files
-    path: PathBuf,
+    #[arg(required_unless_present = "from_database")]
+    path: Option<PathBuf>,
+
+    /// Generate plugin groups from the plugins installed in the plugin database instead of a folder
+    #[arg(long, action, conflicts_with_all = ["path", "recurse", "non_interactive"])]
+    from_database: bool,
+
+    /// Generate a plugin group for each vendor of the installed plugins
+    #[arg(long, action, requires = "from_database", conflicts_with_all = ["vendor", "name", "file_name"])]
+    by_vendor: bool,
+
+    /// Merge a vendor into another when generating by vendor, e.g. "Fabfilter=FabFilter"
+    #[arg(long, value_name = "ALIAS=VENDOR", requires = "by_vendor")]
+    alias: Vec<String>,
+
+    /// Skip vendors with fewer installed plugins than this when generating by vendor
+    #[arg(long, default_value_t = 1, requires = "by_vendor")]
+    min_plugins: usize,
+
+    /// Only include installed plugins whose vendor contains this text (case-insensitive)
+    #[arg(long, requires = "from_database")]
+    vendor: Option<String>,
+
+    /// Only include installed plugins of this format, e.g. vst3, vst2, clap or fruity
+    #[arg(long, requires = "from_database")]
+    format: Option<String>,
+
+    /// Only include installed plugins whose name matches this glob pattern, e.g. "Waves *"
+    #[arg(long, requires = "from_database")]
+    pattern: Option<String>,
 
     /// Name of the plugin group to generate
     #[arg(long, short)]
@@ -36,20 +69,31 @@ pub struct GenerateSubcommand {
     /// Whether to include all plugins in subdirectories in the plugin group
     #[arg(long, action)]
     recurse: bool,
+
+    /// Skip plugins that can't be classified instead of asking whether they are effects
+    #[arg(long, action)]
+    non_interactive: bool,
 }
 
 impl RunnableCommand for GenerateSubcommand {
     fn run(self, config: &Config) -> Result<()> {
-        let mut plugin_names = Vec::<String>::new();
-        self.get_plugin_names(&self.path, &mut plugin_names)?;
+        if self.from_database {
+            return self.generate_from_database(config);
+        }
+
+        let path = self
+            .path
+            .as_deref()
+            .ok_or_else(|| eyre!("no folder containing VST files was given"))?;
+
+        let mut plugin_paths = Vec::<PathBuf>::new();
+        self.get_plugin_paths(path, &mut plugin_paths)?;
 
-        let plugin_count = plugin_names.len();
-        if plugin_count == 0 {
+        if plugin_paths.is_empty() {
             return Err(eyre!("no plugins found in folder"));
         }
 
-        let dir_name = self
-            .path
+        let dir_name = path
             .file_name()
             .ok_or_else(|| eyre!("failed to get directory file name"))?
             .to_str()
@@ -61,39 +105,84 @@ impl RunnableCommand for GenerateSubcommand {
             .clone()
             .unwrap_or_else(|| dir_name.to_lowercase().replace(' ', "_"));
 
-        // Prompt the user to select effect plugins, the non-selected plugins are generator plugins
-        let chosen = MultiSelect::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select the plugins that are effects (SPACE: select, A: select all, ENTER: confirm)")
-            .items(&plugin_names)
-            .interact()?;
+        // Plugins that FL Studio has scanned are resolved to their installed names,
+        // and classified using the plugin database
+        let index = match config.plugin_database() {
+            Ok(plugin_database) => Some(PluginPathIndex::new(plugin_database)?),
+            Err(err) => {
+                println!(
+                    "{} {}",
+                    "WARN: Failed to load the plugin database, plugins can't be classified automatically:"
+                        .yellow(),
+                    format!("{err:#}").yellow()
+                );
+                None
+            }
+        };
 
-        // If none are selected, all of the plugins are generators, so the below code to separate the selected
-        // items from the non-selected items can be skipped
-        if chosen.is_empty() {
-            let plugin_count = plugin_names.len();
-            self.save_group(
-                config,
-                PluginGroupType::Generator,
-                &file_name,
-                &PluginGroup::new(&group_name, plugin_names),
-            )?;
-            self.display_saved_count(&file_name, PluginGroupType::Generator, plugin_count);
-            return Ok(());
+        let mut effects = BTreeSet::new();
+        let mut generators = BTreeSet::new();
+        let mut unknown = Vec::new();
+        for plugin_path in plugin_paths {
+            let Some(index) = &index else {
+                unknown.push(Self::plugin_file_stem(&plugin_path)?);
+                continue;
+            };
+
+            let plugins = index.resolve(&plugin_path);
+            if plugins.is_empty() {
+                println!(
+                    "{}{}{}",
+                    "Skipping '".yellow(),
+                    plugin_path.display().blue().bold(),
+                    "' because it hasn't been scanned by FL Studio yet".yellow()
+                );
+                continue;
+            }
+
+            // A single plugin file can contain multiple plugins, e.g. a Waves shell
+            for plugin in plugins {
+                match plugin.group_type {
+                    PluginGroupType::Effect => effects.insert(plugin.name.to_owned()),
+                    PluginGroupType::Generator => generators.insert(plugin.name.to_owned()),
+                };
+            }
         }
 
-        // Collect effects and generators into vectors based on which ones are chosen
-        let chosen_indexes: HashSet<usize> = HashSet::from_iter(chosen.into_iter());
-        let chosen_count = chosen_indexes.len();
-        let mut effects = Vec::with_capacity(chosen_count);
-        let mut generators = Vec::with_capacity(plugin_names.len() - chosen_count);
-        for (i, plugin) in plugin_names.into_iter().enumerate() {
-            if chosen_indexes.contains(&i) {
-                effects.push(plugin);
+        if !unknown.is_empty() {
+            if self.non_interactive {
+                for plugin in &unknown {
+                    println!(
+                        "{}{}{}",
+                        "Skipping '".yellow(),
+                        plugin.blue().bold(),
+                        "' because it can't be classified".yellow()
+                    );
+                }
             } else {
-                generators.push(plugin);
+                // Prompt the user to select effect plugins, the non-selected plugins are generator plugins
+                let chosen = MultiSelect::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Select the plugins that are effects (SPACE: select, A: select all, ENTER: confirm)")
+                    .items(&unknown)
+                    .interact()?;
+
+                let chosen_indexes: HashSet<usize> = HashSet::from_iter(chosen);
+                for (i, plugin) in unknown.into_iter().enumerate() {
+                    if chosen_indexes.contains(&i) {
+                        effects.insert(plugin);
+                    } else {
+                        generators.insert(plugin);
+                    }
+                }
             }
         }
 
+        if effects.is_empty() && generators.is_empty() {
+            return Err(eyre!(
+                "no plugins could be classified as effects or generators"
+            ));
+        }
+
         // Save effect and generator groups to files
         let effect_count = effects.len();
         let generator_count = generators.len();
@@ -103,7 +192,7 @@ impl RunnableCommand for GenerateSubcommand {
                 config,
                 PluginGroupType::Effect,
                 &file_name,
-                &PluginGroup::new(&group_name, effects),
+                &PluginGroup::new(&group_name, effects.into_iter().collect()),
             )?;
             self.display_saved_count(&file_name, PluginGroupType::Effect, effect_count);
         }
@@ -113,7 +202,7 @@ impl RunnableCommand for GenerateSubcommand {
                 config,
                 PluginGroupType::Generator,
                 &file_name,
-                &PluginGroup::new(&group_name, generators),
+                &PluginGroup::new(&group_name, generators.into_iter().collect()),
             )?;
             self.display_saved_count(&file_name, PluginGroupType::Generator, generator_count);
         }
@@ -123,6 +212,237 @@ impl RunnableCommand for GenerateSubcommand {
 }
 
 impl GenerateSubcommand {
+    /// Generates a plugin group for each `PluginGroupType` from the installed
+    /// plugins in the plugin database that match the filters
+    fn generate_from_database(&self, config: &Config) -> Result<()> {
+        if self.by_vendor {
+            return self.generate_by_vendor(config);
+        }
+
+        let group_name = self
+            .name
+            .clone()
+            .or_else(|| self.vendor.clone())
+            .ok_or_else(|| eyre!("a name is required when generating without --vendor"))?;
+        let file_name = self
+            .file_name
+            .clone()
+            .unwrap_or_else(|| group_name.to_lowercase().replace(' ', "_"));
+
+        let vendor = self.vendor.as_ref().map(|vendor| vendor.to_lowercase());
+        let plugin_database = config.plugin_database()?;
+        let mut saved = false;
+        for database_group in [&plugin_database.effects, &plugin_database.generators] {
+            let mut plugins = Vec::new();
+            for (name, path) in self.installed_plugins(database_group)? {
+                // Files that can't be parsed have no known vendor, so they never match
+                if let Some(vendor) = &vendor {
+                    let plugin_vendor = Self::plugin_vendor(&path).map(|v| v.to_lowercase());
+                    if plugin_vendor.is_none_or(|plugin_vendor| !plugin_vendor.contains(vendor)) {
+                        continue;
+                    }
+                }
+
+                plugins.push(name);
+            }
+
+            if plugins.is_empty() {
+                continue;
+            }
+
+            let plugin_count = plugins.len();
+            let group_type = database_group.group_type.clone();
+            self.save_group(
+                config,
+                group_type.clone(),
+                &file_name,
+                &PluginGroup::new(&group_name, plugins),
+            )?;
+            self.display_saved_count(&file_name, group_type, plugin_count);
+            saved = true;
+        }
+
+        if !saved {
+            return Err(eyre!("no installed plugins match the given filters"));
+        }
+
+        Ok(())
+    }
+
+    /// Generates a plugin group for each vendor of the installed plugins
+    /// in the plugin database that match the filters
+    fn generate_by_vendor(&self, config: &Config) -> Result<()> {
+        let aliases = self
+            .alias
+            .iter()
+            .map(|alias| {
+                alias
+                    .split_once('=')
+                    .map(|(alias, vendor)| (alias.trim().to_owned(), vendor.trim().to_owned()))
+                    .ok_or_else(|| eyre!("invalid alias '{alias}', expected ALIAS=VENDOR"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let plugin_database = config.plugin_database()?;
+        let mut saved = false;
+        for database_group in [&plugin_database.effects, &plugin_database.generators] {
+            let mut vendor_plugins = Vec::new();
+            let mut unknown_count = 0;
+            for (name, path) in self.installed_plugins(database_group)? {
+                match Self::plugin_vendor(&path) {
+                    Some(vendor) => vendor_plugins.push((vendor, name)),
+                    None => unknown_count += 1,
+                }
+            }
+
+            if unknown_count > 0 {
+                println!(
+                    "{} {} {}",
+                    "Skipping".yellow(),
+                    unknown_count.blue().bold(),
+                    format!(
+                        "{} plugin{} without a vendor",
+                        database_group.group_type.name(),
+                        if unknown_count == 1 { "" } else { "s" }
+                    )
+                    .yellow()
+                );
+            }
+
+            let vendors = group_by_vendor(
+                vendor_plugins
+                    .iter()
+                    .map(|(vendor, name)| (vendor.as_str(), name.as_str())),
+                &aliases,
+            );
+            for (vendor, plugins) in vendors {
+                if plugins.len() < self.min_plugins {
+                    continue;
+                }
+
+                let group_name = sanitize_name(&vendor);
+                if let Err(err) = validate_name(&group_name) {
+                    println!(
+                        "{}{}{}{}",
+                        "Skipping '".yellow(),
+                        vendor.blue().bold(),
+                        "' because it can't be used as a folder name, use --alias to rename it: "
+                            .yellow(),
+                        format!("{err:#}").yellow()
+                    );
+                    continue;
+                }
+
+                // Existing groups may have been written by hand, so they aren't replaced silently
+                let file_name = group_name.to_lowercase().replace(' ', "_");
+                let group_type = database_group.group_type.clone();
+                if !self.confirm_overwrite(config, &group_type, &file_name)? {
+                    continue;
+                }
+
+                let plugin_count = plugins.len();
+                self.save_group(
+                    config,
+                    group_type.clone(),
+                    &file_name,
+                    &PluginGroup::new(&group_name, plugins.into_iter().collect()),
+                )?;
+                self.display_saved_count(&file_name, group_type, plugin_count);
+                saved = true;
+            }
+        }
+
+        if !saved {
+            return Err(eyre!("no vendors have enough installed plugins"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name and [`Path`] of every installed plugin of a `PluginDatabaseGroup`
+    /// that matches the `format` and `pattern` filters, sorted by name
+    fn installed_plugins(
+        &self,
+        database_group: &PluginDatabaseGroup,
+    ) -> Result<BTreeMap<String, PathBuf>> {
+        let pattern = self
+            .pattern
+            .as_ref()
+            .map(|pattern| {
+                PluginMatcher {
+                    glob: vec![pattern.to_owned()],
+                    ..Default::default()
+                }
+                .compile()
+            })
+            .transpose()?;
+
+        let mut plugins = BTreeMap::new();
+        for folder in &database_group.installed.folders {
+            if self
+                .format
+                .as_ref()
+                .is_some_and(|format| !format.eq_ignore_ascii_case(&folder.format()))
+            {
+                continue;
+            }
+
+            for path in folder.plugin_files()? {
+                let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+
+                if plugins.contains_key(name)
+                    || pattern
+                        .as_ref()
+                        .is_some_and(|pattern| !pattern.is_match(name))
+                {
+                    continue;
+                }
+
+                plugins.insert(name.to_owned(), path.to_owned());
+            }
+        }
+
+        Ok(plugins)
+    }
+
+    /// Returns the vendor stored in an installed plugin's `.fst` file,
+    /// or [`None`] if the file has no vendor or can't be parsed
+    fn plugin_vendor(path: &Path) -> Option<String> {
+        FstFile::from_file(path)
+            .ok()
+            .and_then(|fst| fst.vendor)
+            .filter(|vendor| !vendor.trim().is_empty())
+    }
+
+    /// Asks whether an existing plugin group file should be overwritten,
+    /// returning true if the file doesn't exist yet
+    fn confirm_overwrite(
+        &self,
+        config: &Config,
+        group_type: &PluginGroupType,
+        file_name: &str,
+    ) -> Result<bool> {
+        let group = match group_type {
+            PluginGroupType::Effect => &config.effects,
+            PluginGroupType::Generator => &config.generators,
+        };
+
+        if !group.group_exists(file_name) {
+            return Ok(true);
+        }
+
+        let overwrite = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "The {} plugin group file '{file_name}.toml' already exists. Do you want to overwrite it?",
+                group_type.name()
+            ))
+            .default(false)
+            .interact()?;
+        Ok(overwrite)
+    }
+
     fn save_group(
         &self,
         config: &Config,
@@ -140,12 +460,12 @@ impl GenerateSubcommand {
             .wrap_err_with(|| eyre!("failed to save {} plugin group", group_type.name()))
     }
 
-    fn get_plugin_names(&self, dir: &Path, plugin_names: &mut Vec<String>) -> Result<()> {
+    fn get_plugin_paths(&self, dir: &Path, plugin_paths: &mut Vec<PathBuf>) -> Result<()> {
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_dir() && self.recurse {
-                self.get_plugin_names(&path, plugin_names)?;
+                self.get_plugin_paths(&path, plugin_paths)?;
                 continue;
             }
 
@@ -153,18 +473,21 @@ impl GenerateSubcommand {
                 continue;
             }
 
-            plugin_names.push(
-                path.file_stem()
-                    .ok_or_else(|| eyre!("failed to get file name of plugin"))?
-                    .to_str()
-                    .ok_or_else(|| eyre!("failed to convert file name of plugin to string"))?
-                    .to_owned(),
-            )
+            plugin_paths.push(path);
         }
 
         Ok(())
     }
 
+    fn plugin_file_stem(path: &Path) -> Result<String> {
+        Ok(path
+            .file_stem()
+            .ok_or_else(|| eyre!("failed to get file name of plugin"))?
+            .to_str()
+            .ok_or_else(|| eyre!("failed to convert file name of plugin to string"))?
+            .to_owned())
+    }
+
     fn display_saved_count(
         &self,
         file_name: &str,
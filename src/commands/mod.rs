@@ -1,13 +1,23 @@
-use clap::Parser;
-use color_eyre::Result;
+use clap::{Parser, ValueEnum};
+use color_eyre::{eyre::Context, Result};
+use dialoguer::{theme::ColorfulTheme, FuzzySelect};
+use owo_colors::OwoColorize;
 
-use crate::config::Config;
+use fl_plugin_sorter::{
+    config::{Config, ConfigWarning, PluginGroup, PluginGroupData},
+    plan::{Action, Plan, Skipped},
+};
 
 use self::{
-    generate::GenerateSubcommand, list::ListSubcommand, new::NewSubcommand, sort::SortSubcommand,
-    unsort::UnsortSubcommand,
+    coverage::CoverageSubcommand, doctor::DoctorSubcommand, edit::EditSubcommand,
+    fix::FixSubcommand, generate::GenerateSubcommand, list::ListSubcommand, new::NewSubcommand,
+    sort::SortSubcommand, unsort::UnsortSubcommand,
 };
 
+mod coverage;
+mod doctor;
+mod edit;
+mod fix;
 mod generate;
 mod list;
 mod new;
@@ -16,6 +26,10 @@ mod unsort;
 
 #[derive(Debug, Parser)]
 pub enum Subcommand {
+    Coverage(CoverageSubcommand),
+    Doctor(DoctorSubcommand),
+    Edit(EditSubcommand),
+    Fix(FixSubcommand),
     Generate(GenerateSubcommand),
     List(ListSubcommand),
     New(NewSubcommand),
@@ -26,3 +40,198 @@ pub enum Subcommand {
 pub trait RunnableCommand {
     fn run(self, config: &Config) -> Result<()>;
 }
+
+/// Prompts the user to select one of the plugin groups of the [`Config`],
+/// returning `None` if there are none or the prompt was cancelled
+fn select_group(config: &Config) -> Result<Option<(&PluginGroupData, &PluginGroup)>> {
+    let mut groups = Vec::new();
+    for group_data in [&config.effects, &config.generators] {
+        let type_name = group_data.group_type.name().to_uppercase();
+        for group in &group_data.groups {
+            let name = format!("{} ({type_name})", group.folder().display());
+            groups.push((name, group_data, group));
+        }
+    }
+
+    if groups.is_empty() {
+        println!("{}", "There are no plugin groups defined".bright_red());
+        return Ok(None);
+    }
+
+    groups.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+    let names: Vec<&String> = groups.iter().map(|(name, ..)| name).collect();
+
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select a plugin group, type to search")
+        .items(&names)
+        .max_length(5)
+        .interact_opt()?;
+
+    Ok(selection
+        .and_then(|i| groups.get(i))
+        .map(|(_, group_data, group)| (*group_data, *group)))
+}
+
+/// Displays the warnings found when loading the [`Config`].
+///
+/// Plugin group files that failed to load are displayed together, after any other warnings.
+/// Warnings are written to stderr, so they never mix with output such as JSON reports.
+pub fn display_config_warnings(config: &Config) {
+    let mut invalid_groups = Vec::new();
+    for warning in &config.warnings {
+        match warning {
+            ConfigWarning::DuplicateGroup { folder, path } => eprintln!(
+                "{}{}{}{}",
+                "WARN: A plugin group sorted into '".yellow(),
+                folder.display().blue(),
+                "' already exists. Merging with the group defined in ".yellow(),
+                path.display().blue()
+            ),
+            ConfigWarning::InvalidGroup(error) => invalid_groups.push(error),
+        }
+    }
+
+    if invalid_groups.is_empty() {
+        return;
+    }
+
+    let count = invalid_groups.len();
+    eprintln!(
+        "{} {} {}",
+        "WARN: Skipped".yellow(),
+        count.blue().bold(),
+        format!(
+            "plugin group file{} that failed to load:",
+            if count == 1 { "" } else { "s" }
+        )
+        .yellow()
+    );
+    for error in invalid_groups {
+        eprintln!("  {}", error.yellow());
+    }
+}
+
+/// Represents the different formats a [`Plan`] or report can be displayed in
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum OutputFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+/// Displays the actions of a [`Plan`] without applying them
+fn display_plan(plan: &Plan, format: OutputFormat) -> Result<()> {
+    if let OutputFormat::Json = format {
+        let json = serde_json::to_string_pretty(plan).wrap_err("failed to serialize plan")?;
+        println!("{json}");
+        return Ok(());
+    }
+
+    display_skipped(&plan.skipped);
+    if plan.actions.is_empty() {
+        println!("{}", "Nothing to do.".green());
+        return Ok(());
+    }
+
+    for action in &plan.actions {
+        match action {
+            Action::CreateDir { path } => {
+                println!("{} {}", "create dir".green().bold(), path.display())
+            }
+            Action::Place {
+                from,
+                to,
+                placement,
+            } => println!(
+                "{} {} -> {}",
+                format!("{:<10}", placement.name()).green().bold(),
+                from.display(),
+                to.display()
+            ),
+            Action::Overwrite {
+                from,
+                to,
+                placement,
+            } => println!(
+                "{} {} -> {} ({})",
+                "overwrite ".yellow().bold(),
+                from.display(),
+                to.display(),
+                placement.name()
+            ),
+            Action::Delete { path } => println!("{} {}", "delete    ".red().bold(), path.display()),
+            Action::RemoveDir { path } => {
+                println!("{} {}", "remove dir".red().bold(), path.display())
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Displays everything that was skipped when creating a [`Plan`]
+fn display_skipped(skipped: &[Skipped]) {
+    for skipped in skipped {
+        match skipped {
+            Skipped::EmptyGroup { group } => println!(
+                "{}{}{}",
+                "Skipping '".green(),
+                group.cyan().bold(),
+                "' because no plugins are defined".green()
+            ),
+            Skipped::NotInstalled {
+                plugin,
+                suggestions,
+                ..
+            } => {
+                print!(
+                    "{}{}{}",
+                    "Skipping '".yellow(),
+                    plugin.blue().bold(),
+                    "' because it is not installed".yellow()
+                );
+
+                if suggestions.is_empty() {
+                    println!();
+                } else {
+                    let suggestions: Vec<String> = suggestions
+                        .iter()
+                        .map(|suggestion| format!("'{}'", suggestion.cyan()))
+                        .collect();
+                    println!(
+                        "{}{}{}",
+                        ", did you mean ".yellow(),
+                        suggestions.join(", "),
+                        "?".yellow()
+                    );
+                }
+            }
+            Skipped::Conflict {
+                group,
+                plugin,
+                placed_in,
+            } => println!(
+                "{}{}{}{}{}{}{}",
+                "Skipping '".green(),
+                plugin.blue().bold(),
+                "' in '".green(),
+                group.cyan().bold(),
+                "' because it is sorted into '".green(),
+                placed_in.cyan().bold(),
+                "'".green()
+            ),
+            Skipped::Changed { path } => println!(
+                "{}{}{}",
+                "Skipping '".yellow(),
+                path.display().blue().bold(),
+                "' because it has changed since it was sorted".yellow()
+            ),
+            Skipped::Unmanaged { path } => println!(
+                "{}{}{}",
+                "Skipping '".yellow(),
+                path.display().blue().bold(),
+                "' because it already exists and wasn't created when sorting".yellow()
+            ),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use clap::Parser;
 use color_eyre::Result;
 use dialoguer::{theme::ColorfulTheme, Confirm};
 
-use crate::config::{Config, PluginGroup, PluginGroupType};
+use fl_plugin_sorter::config::{Config, PluginGroup, PluginGroupType};
 
 use super::RunnableCommand;
 
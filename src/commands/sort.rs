@@ -1,54 +1,68 @@
-use std::{fs, path::Path};
-
 use clap::Parser;
-use color_eyre::{
-    eyre::{eyre, Context},
-    Result,
-};
+use color_eyre::Result;
 use owo_colors::OwoColorize;
 
-use crate::config::{Config, InstalledPlugins, PluginGroup};
+use fl_plugin_sorter::{
+    config::{Config, Placement},
+    plan::{Plan, SortOptions},
+    sorter::sort,
+};
 
-use super::RunnableCommand;
-
-struct SortResult {
-    folder_count: u32,
-    plugin_count: u32,
-}
+use super::{display_plan, display_skipped, OutputFormat, RunnableCommand};
 
 #[derive(Debug, Parser)]
 /// Sorts plugins defined by plugin groups into the plugin database
-pub struct SortSubcommand {}
+pub struct SortSubcommand {
+    /// Display the changes that would be made without making them
+    #[arg(long, action)]
+    dry_run: bool,
+
+    /// Format used to display the changes of a dry run
+    #[arg(long, value_enum, default_value_t, requires = "dry_run")]
+    format: OutputFormat,
+
+    /// Also remove sorted plugins and folders that are no longer defined by a plugin group
+    #[arg(long, action)]
+    mirror: bool,
+
+    /// How plugin files are placed into plugin group folders, overriding the user config
+    #[arg(long, value_enum)]
+    placement: Option<Placement>,
+}
 
 impl RunnableCommand for SortSubcommand {
     fn run(self, config: &Config) -> Result<()> {
-        let is_effects_empty = config.effects.groups.is_empty();
-        let is_generators_empty = config.generators.groups.is_empty();
-        if is_effects_empty && is_generators_empty {
-            return Err(eyre!("there are no plugin groups to sort"));
+        let options = SortOptions {
+            placement: self.placement.unwrap_or(config.user.placement),
+            both: config.user.both,
+            mirror: self.mirror,
+        };
+
+        let result = sort(config, options, self.dry_run)?;
+        if result.dry_run {
+            return display_plan(&result.combined_plan(), self.format);
         }
 
-        let plugin_database = &config.plugin_database;
-        if !is_effects_empty {
-            self.display_result(
-                self.sort_groups(
-                    &plugin_database.effects.folder,
-                    &plugin_database.effects.installed,
-                    &config.effects.groups,
-                )?,
-                "effect",
-            );
+        for type_result in &result.types {
+            display_skipped(&type_result.plan.skipped);
+            self.display_result(&type_result.plan, &type_result.group_type.name());
+
+            let settings = config.user.type_settings(&type_result.group_type);
+            if let (Some(unsorted), Some(unsorted_folder)) =
+                (&settings.unsorted, settings.unsorted_folder())
+            {
+                let unsorted_dir = config
+                    .plugin_database()?
+                    .get_group(&type_result.group_type)
+                    .folder
+                    .join(unsorted_folder);
+                let unsorted_count = type_result.plan.placed_count_in(&unsorted_dir);
+                self.display_unsorted(unsorted_count, unsorted, &type_result.group_type.name());
+            }
         }
 
-        if !is_generators_empty {
-            self.display_result(
-                self.sort_groups(
-                    &plugin_database.generators.folder,
-                    &plugin_database.generators.installed,
-                    &config.generators.groups,
-                )?,
-                "generator",
-            );
+        if self.mirror {
+            self.display_mirror(&result.mirror);
         }
 
         Ok(())
@@ -56,69 +70,52 @@ impl RunnableCommand for SortSubcommand {
 }
 
 impl SortSubcommand {
-    fn sort_groups(
-        &self,
-        plugin_folder: &Path,
-        installed_plugins: &InstalledPlugins,
-        groups: &Vec<PluginGroup>,
-    ) -> Result<SortResult> {
-        let mut result = SortResult {
-            folder_count: 0,
-            plugin_count: 0,
-        };
-
-        for group in groups {
-            if group.plugins.is_empty() {
-                println!(
-                    "{}{}{}",
-                    "Skipping '".green(),
-                    group.name.cyan().bold(),
-                    "' because no plugins are defined".green()
-                );
-                continue;
-            }
-
-            let group_dir = plugin_folder.join(&group.name);
-            fs::create_dir_all(&group_dir).wrap_err("failed to create group directory")?;
-
-            // Copy over plugins to group folder
-            for plugin_name in &group.plugins {
-                let plugin_path = installed_plugins.get_plugin(plugin_name);
-                if let Some(path) = plugin_path {
-                    let destination = group_dir.join(format!("{plugin_name}.fst"));
-                    fs::copy(path, destination)
-                        .wrap_err_with(|| format!("failed to copy '{plugin_name}'"))?;
-
-                    result.plugin_count += 1;
-                } else {
-                    println!(
-                        "{}{}{}",
-                        "Skipping '".yellow(),
-                        plugin_name.blue().bold(),
-                        "' because it is not installed".yellow()
-                    )
-                }
-            }
+    fn display_mirror(&self, plan: &Plan) {
+        display_skipped(&plan.skipped);
 
-            result.folder_count += 1;
-        }
+        let removed_count = plan.deleted_count();
+        println!(
+            "{} {} {}",
+            "Removed".green(),
+            removed_count.cyan().bold(),
+            format!(
+                "plugin{} no longer in a plugin group",
+                if removed_count == 1 { "" } else { "s" }
+            )
+            .green()
+        );
+    }
 
-        Ok(result)
+    fn display_unsorted(&self, unsorted_count: usize, unsorted: &str, plugin_type: &str) {
+        println!(
+            "{} {} {} {}",
+            "Found".green(),
+            unsorted_count.cyan().bold(),
+            format!(
+                "{} plugin{} not in a plugin group, sorted into",
+                plugin_type,
+                if unsorted_count == 1 { "" } else { "s" }
+            )
+            .green(),
+            unsorted.cyan().bold()
+        );
     }
 
-    fn display_result(&self, result: SortResult, plugin_type: &str) {
+    fn display_result(&self, plan: &Plan, plugin_type: &str) {
+        let plugin_count = plan.placed_count();
+        let folder_count = plan.folder_count();
         println!(
             "{} {} {} {} {}",
             "Successfully sorted".green(),
-            result.plugin_count.cyan().bold(),
+            plugin_count.cyan().bold(),
             format!(
                 "{} plugin{} into",
                 plugin_type,
-                if result.plugin_count == 1 { "" } else { "s" }
+                if plugin_count == 1 { "" } else { "s" }
             )
             .green(),
-            result.folder_count.cyan().bold(),
-            format!("folder{}", if result.folder_count == 1 { "" } else { "s" }).green()
+            folder_count.cyan().bold(),
+            format!("folder{}", if folder_count == 1 { "" } else { "s" }).green()
         );
     }
 }
@@ -1,20 +1,43 @@
 use std::{
-    collections::HashSet,
-    fs,
+    cell::OnceCell,
+    collections::{BTreeSet, HashSet},
+    env, fmt, fs,
     path::{Path, PathBuf},
 };
 
-use clap::ValueEnum;
 use color_eyre::{
     eyre::{eyre, Context},
-    Result,
+    Report, Result,
 };
-use dirs::document_dir;
-use owo_colors::OwoColorize;
+use dirs::{document_dir, home_dir};
 use serde::{Deserialize, Serialize};
 
+use crate::{matcher::PluginMatcher, validate::validate_name};
+
+/// Returns the default config directory.
+///
+/// This is `$XDG_CONFIG_HOME/flsorter` if `XDG_CONFIG_HOME` is set,
+/// otherwise it is `~/.config/flsorter`.
+///
+/// # Errors
+/// The function will return an error if `XDG_CONFIG_HOME` is not set
+/// and the home directory could not be found.
+pub fn default_config_dir() -> Result<PathBuf> {
+    if let Some(config_home) = env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(config_home).join("flsorter"));
+    }
+
+    let home = home_dir().ok_or_else(|| eyre!("failed to get home directory"))?;
+    if !home.exists() {
+        return Err(eyre!("home directory does not exist"));
+    }
+
+    Ok(home.join(".config/flsorter"))
+}
+
 /// Represents the different types of possible plugin groups
-#[derive(ValueEnum, Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum PluginGroupType {
     Effect,
     Generator,
@@ -39,20 +62,92 @@ impl PluginGroupType {
 /// A data structure that defines the name of a
 /// group of plugins and a list containing the names
 /// of plugins that should be sorted into that group.
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// Installed plugins can also be added to the group through
+/// `include` rules, and removed from the rule matches through
+/// `exclude` rules.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PluginGroup {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default)]
     pub plugins: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefer: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub both: Option<bool>,
+    /// Decides which group a plugin is placed in when it is in several groups and
+    /// the type is `exclusive`, where the highest priority wins. Defaults to 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    // Rules are TOML tables, so they have to be serialized after every other value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<PluginMatcher>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<PluginMatcher>,
+    /// The folder containing the group file, relative to the folder of its `PluginGroupType`
+    #[serde(skip)]
+    pub directory: PathBuf,
+    /// The group file the group was loaded from, which is empty if it hasn't been saved
+    #[serde(skip)]
+    pub file_path: PathBuf,
 }
 
 impl PluginGroup {
     pub fn new(name: &str, plugins: Vec<String>) -> Self {
         Self {
             name: name.to_owned(),
+            path: None,
             plugins,
+            include: None,
+            exclude: None,
+            prefer: None,
+            both: None,
+            priority: None,
+            directory: PathBuf::new(),
+            file_path: PathBuf::new(),
         }
     }
 
+    /// Returns the path of the group's folder, relative to the plugin database folder
+    /// of its `PluginGroupType`.
+    ///
+    /// This is the `path` of the group, where each folder is separated by `/`,
+    /// or the name of the group if no `path` is defined. Either is placed inside
+    /// the `directory` containing the group file.
+    pub fn folder(&self) -> PathBuf {
+        match &self.path {
+            Some(path) => self.directory.join(path.split('/').collect::<PathBuf>()),
+            None => self.directory.join(&self.name),
+        }
+    }
+
+    /// Returns the names of all plugins in the group.
+    ///
+    /// This contains the plugins listed in `plugins`, followed by any of the
+    /// `installed` plugins that match the `include` rules but not the `exclude` rules.
+    pub fn resolve_plugins(&self, installed: &[String]) -> Result<Vec<String>> {
+        let mut plugins = self.plugins.clone();
+        let include = match &self.include {
+            Some(include) => include.compile()?,
+            None => return Ok(plugins),
+        };
+
+        let exclude = self.exclude.as_ref().map(|m| m.compile()).transpose()?;
+        for name in installed {
+            if !include.is_match(name) || exclude.as_ref().is_some_and(|m| m.is_match(name)) {
+                continue;
+            }
+
+            if !plugins.contains(name) {
+                plugins.push(name.to_owned());
+            }
+        }
+
+        Ok(plugins)
+    }
+
     /// Creates a [`PluginGroup`] from a [`Path`] pointing to
     /// a TOML file through deserialization.
     fn from_file(path: &Path) -> Result<Self> {
@@ -69,7 +164,49 @@ impl PluginGroup {
         let contents = fs::read_to_string(path)
             .wrap_err_with(|| eyre!("failed to read contents of {}", file_name))?;
 
-        toml::from_str(&contents).wrap_err_with(|| eyre!("failed to parse {}", file_name))
+        let mut group: Self =
+            toml::from_str(&contents).wrap_err_with(|| eyre!("failed to parse {}", file_name))?;
+        group.file_path = path.to_owned();
+
+        group
+            .validate()
+            .wrap_err_with(|| eyre!("invalid plugin group in {}", file_name))?;
+
+        Ok(group)
+    }
+
+    /// Saves the group to a TOML file at the given [`Path`], after validating it
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.validate()
+            .wrap_err_with(|| eyre!("failed to validate '{}'", self.name))?;
+
+        let contents = toml::to_string(self)
+            .wrap_err_with(|| eyre!("failed to convert '{}' into a TOML string", self.name))?;
+
+        fs::write(path, contents)
+            .wrap_err_with(|| eyre!("failed to write '{}' to a file", self.name))
+    }
+
+    /// Ensures the names of the group and its plugins can be used inside the
+    /// plugin database, and that the `include` and `exclude` rules compile.
+    pub fn validate(&self) -> Result<()> {
+        validate_name(&self.name).wrap_err("invalid plugin group name")?;
+        if let Some(path) = &self.path {
+            for folder in path.split('/') {
+                validate_name(folder)
+                    .wrap_err_with(|| eyre!("invalid plugin group path '{path}'"))?;
+            }
+        }
+
+        for plugin in &self.plugins {
+            validate_name(plugin).wrap_err("invalid plugin name")?;
+        }
+
+        for matcher in [&self.include, &self.exclude].into_iter().flatten() {
+            matcher.compile().wrap_err("invalid rules")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -104,66 +241,150 @@ impl PluginGroupData {
         path.exists() && path.is_file()
     }
 
+    /// Saves a `PluginGroup` to a file in the folder of its `directory`,
+    /// which is the top-level folder of the type for new plugin groups
     pub fn save_group(&self, file_name: &str, group: &PluginGroup) -> Result<()> {
-        let file_path = self.group_path(file_name);
-        let contents = toml::to_string(group)
-            .wrap_err_with(|| eyre!("failed to convert '{}' into a TOML string", group.name))?;
+        validate_name(file_name).wrap_err("invalid plugin group file name")?;
+        let path = self
+            .config_path
+            .join(&group.directory)
+            .join(format!("{file_name}.toml"));
+        group.save(&path)
+    }
+}
 
-        fs::write(file_path, contents)
-            .wrap_err_with(|| eyre!("failed to write '{}' to a file", group.name))
+/// A folder containing installed plugin files of a single
+/// plugin format, such as `VST3`, `CLAP` or `Fruity`.
+#[derive(Debug)]
+pub struct InstalledFolder {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl InstalledFolder {
+    /// Returns the name of the plugin format stored in the folder,
+    /// which is matched against the `prefer` list of the user config.
+    ///
+    /// FL Studio stores VST2 plugins in the `VST` folder, so it is named `vst2`.
+    pub fn format(&self) -> String {
+        match self.name.to_lowercase().as_str() {
+            "vst" => "vst2".to_owned(),
+            name => name.to_owned(),
+        }
+    }
+
+    /// Returns the [`Path`] of every `.fst` file in the folder
+    pub fn plugin_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.path)
+            .wrap_err_with(|| eyre!("failed to read {}", self.path.display()))?
+        {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "fst") {
+                files.push(path);
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Returns the position of the folder's format in `prefer`,
+    /// or the length of `prefer` if it isn't in the list.
+    pub fn priority(&self, prefer: &[String]) -> usize {
+        let format = self.format();
+        prefer
+            .iter()
+            .position(|preferred| preferred.eq_ignore_ascii_case(&format))
+            .unwrap_or(prefer.len())
     }
 }
 
-/// Contains two [`Path`]s pointing to a directory
-/// containing installed plugin files.
+/// Contains the [`Path`]s of every folder containing
+/// installed plugin files, e.g. `VST`, `VST3` and `CLAP`.
 ///
-/// These folders contain either `VST3` plugins or
-/// `VST` plugins pointed to in `.fst` files.
+/// The folders are ordered by priority, so when a plugin exists in
+/// multiple folders, the file in the first folder is used.
 #[derive(Debug)]
 pub struct InstalledPlugins {
-    pub vst: PathBuf,
-    pub vst3: PathBuf,
+    pub folders: Vec<InstalledFolder>,
 }
 
 impl InstalledPlugins {
-    fn new(vst: &Path, vst3: &Path) -> Self {
-        Self {
-            vst: vst.to_owned(),
-            vst3: vst3.to_owned(),
-        }
+    fn new(folders: Vec<InstalledFolder>) -> Self {
+        Self { folders }
     }
 
-    /// Creates a `InstalledPlugins` from a [`Path`].
+    /// Creates a `InstalledPlugins` from every subdirectory of a [`Path`].
     ///
-    /// # Errors
-    /// The function will return an error if the given [`Path`] does not contain
-    /// a `VST` or `VST3` subdirectory.
-    fn from_folder(plugin_folder: &Path) -> Result<Self> {
-        let vst3 = plugin_folder.join("VST3");
-        let vst = plugin_folder.join("VST");
+    /// The subdirectories are ordered by the position of their format in `prefer`,
+    /// followed by any formats that aren't in `prefer` alphabetically.
+    /// If the [`Path`] does not exist, there are no installed plugins.
+    fn from_folder(plugin_folder: &Path, prefer: &[String]) -> Result<Self> {
+        let mut folders = Vec::new();
+        if !plugin_folder.is_dir() {
+            return Ok(Self::new(folders));
+        }
 
-        if (!vst3.exists() || !vst.exists()) || (!vst3.is_dir() || !vst.is_dir()) {
-            return Err(eyre!(
-                "installed plugins folder does not contain VST or VST3 folders"
-            ));
+        for entry in fs::read_dir(plugin_folder)
+            .wrap_err_with(|| eyre!("failed to read {}", plugin_folder.display()))?
+        {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+                folders.push(InstalledFolder {
+                    name: name.to_owned(),
+                    path: path.to_owned(),
+                });
+            }
         }
 
-        Ok(Self::new(&vst, &vst3))
+        folders.sort_by_cached_key(|folder| (folder.priority(prefer), folder.format()));
+
+        Ok(Self::new(folders))
     }
 
-    pub fn get_plugin(&self, name: &str) -> Option<PathBuf> {
-        let file_name = format!("{name}.fst");
-        let vst3 = self.vst3.join(&file_name);
-        if vst3.exists() {
-            return Some(vst3);
+    /// Returns the names of all installed plugins, sorted alphabetically.
+    ///
+    /// The name of a plugin is the file stem of its `.fst` file.
+    pub fn plugin_names(&self) -> Result<Vec<String>> {
+        let mut names = BTreeSet::new();
+        for folder in &self.folders {
+            for path in folder.plugin_files()? {
+                if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    names.insert(name.to_owned());
+                }
+            }
         }
 
-        let vst = self.vst.join(&file_name);
-        if vst.exists() {
-            return Some(vst);
+        Ok(names.into_iter().collect())
+    }
+
+    /// Returns every format an installed plugin's `.fst` file exists in,
+    /// along with its [`Path`].
+    ///
+    /// The formats are ordered by `prefer` if one is given, otherwise
+    /// the priority of the folders is used.
+    pub fn get_plugin_formats(
+        &self,
+        name: &str,
+        prefer: Option<&[String]>,
+    ) -> Vec<(&InstalledFolder, PathBuf)> {
+        let file_name = format!("{name}.fst");
+        let mut formats: Vec<(&InstalledFolder, PathBuf)> = self
+            .folders
+            .iter()
+            .map(|folder| (folder, folder.path.join(&file_name)))
+            .filter(|(_, path)| path.is_file())
+            .collect();
+
+        if let Some(prefer) = prefer {
+            formats.sort_by_key(|(folder, _)| folder.priority(prefer));
         }
 
-        None
+        formats
     }
 }
 
@@ -194,7 +415,7 @@ pub struct PluginDatabase {
 }
 
 impl PluginDatabase {
-    fn new(database_path: &Path) -> Result<Self> {
+    fn new(database_path: &Path, prefer: &[String]) -> Result<Self> {
         // Check an array of paths, all paths must exist
         // to ensure it is valid
         let effects = database_path.join("Effects");
@@ -216,33 +437,143 @@ impl PluginDatabase {
         Ok(Self {
             effects: PluginDatabaseGroup::new(
                 PluginGroupType::Effect,
-                InstalledPlugins::from_folder(&installed_effects)?,
+                InstalledPlugins::from_folder(&installed_effects, prefer)?,
                 &effects,
             ),
             generators: PluginDatabaseGroup::new(
                 PluginGroupType::Generator,
-                InstalledPlugins::from_folder(&installed_generators)?,
+                InstalledPlugins::from_folder(&installed_generators, prefer)?,
                 &generators,
             ),
         })
     }
 
-    pub fn get_group_path(&self, group: &PluginGroup, group_type: &PluginGroupType) -> PathBuf {
-        let database_group = match group_type {
+    /// Returns the `PluginDatabaseGroup` for the given `PluginGroupType`
+    pub fn get_group(&self, group_type: &PluginGroupType) -> &PluginDatabaseGroup {
+        match group_type {
             PluginGroupType::Effect => &self.effects,
             PluginGroupType::Generator => &self.generators,
-        };
+        }
+    }
+
+    pub fn get_group_path(&self, group: &PluginGroup, group_type: &PluginGroupType) -> PathBuf {
+        self.get_group(group_type).folder.join(group.folder())
+    }
+}
+
+/// Represents the different ways a plugin file can be placed into a plugin group folder
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    /// Copy the installed plugin file
+    #[default]
+    Copy,
+    /// Create a symbolic link to the installed plugin file
+    Symlink,
+    /// Create a hard link to the installed plugin file
+    Hardlink,
+}
+
+impl Placement {
+    /// Returns the name of the placement
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Copy => "copy",
+            Self::Symlink => "symlink",
+            Self::Hardlink => "hardlink",
+        }
+    }
+
+    /// Places the file at `from` at the path `to`, replacing any existing file.
+    ///
+    /// An existing file is always removed first, so that a link is never written through.
+    pub fn place(&self, from: &Path, to: &Path) -> Result<()> {
+        if fs::symlink_metadata(to).is_ok() {
+            fs::remove_file(to).wrap_err_with(|| eyre!("failed to remove {}", to.display()))?;
+        }
+
+        match self {
+            Self::Copy => fs::copy(from, to).map(|_| ()),
+            Self::Symlink => symlink_file(from, to),
+            Self::Hardlink => fs::hard_link(from, to),
+        }
+        .wrap_err_with(|| eyre!("failed to {} {}", self.name(), from.display()))
+    }
+}
+
+#[cfg(unix)]
+fn symlink_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(from, to)
+}
+
+#[cfg(windows)]
+fn symlink_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(from, to)
+}
+
+/// Settings that only apply to the plugin groups of a single `PluginGroupType`
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeSettings {
+    /// The folder that installed plugins not in any plugin group are sorted into,
+    /// where each folder is separated by `/`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsorted: Option<String>,
+    /// Whether a plugin in several plugin groups is only placed in the group with the
+    /// highest `priority`, instead of every group
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exclusive: bool,
+}
+
+impl TypeSettings {
+    fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Returns the path of the `unsorted` folder, relative to the plugin
+    /// database folder of the `PluginGroupType`
+    pub fn unsorted_folder(&self) -> Option<PathBuf> {
+        self.unsorted
+            .as_ref()
+            .map(|unsorted| unsorted.split('/').collect())
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(unsorted) = &self.unsorted {
+            for folder in unsorted.split('/') {
+                validate_name(folder)
+                    .wrap_err_with(|| eyre!("invalid unsorted folder '{unsorted}'"))?;
+            }
+        }
 
-        database_group.folder.join(&group.name)
+        Ok(())
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserConfig {
     pub plugin_database_path: PathBuf,
+    #[serde(default)]
+    pub placement: Placement,
+    #[serde(default = "UserConfig::default_prefer")]
+    pub prefer: Vec<String>,
+    #[serde(default)]
+    pub both: bool,
+    #[serde(default, skip_serializing_if = "TypeSettings::is_default")]
+    pub effect: TypeSettings,
+    #[serde(default, skip_serializing_if = "TypeSettings::is_default")]
+    pub generator: TypeSettings,
 }
 
 impl UserConfig {
+    /// Returns the default priority of plugin formats, preferring VST3 plugins
+    fn default_prefer() -> Vec<String> {
+        ["vst3", "vst2", "clap", "fruity"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
     pub fn new(config_dir: &Path) -> Result<Self> {
         // Ensure config file exists and is valid
         let config_file = config_dir.join("config.toml");
@@ -250,6 +581,10 @@ impl UserConfig {
             let contents =
                 fs::read_to_string(&config_file).wrap_err("failed to read config.toml")?;
             let config: Self = toml::from_str(&contents).wrap_err("failed to parse config.toml")?;
+            for settings in [&config.effect, &config.generator] {
+                settings.validate().wrap_err("invalid config.toml")?;
+            }
+
             return Ok(config);
         }
 
@@ -260,6 +595,11 @@ impl UserConfig {
 
         let config = Self {
             plugin_database_path,
+            placement: Placement::default(),
+            prefer: Self::default_prefer(),
+            both: false,
+            effect: TypeSettings::default(),
+            generator: TypeSettings::default(),
         };
 
         let contents = toml::to_string(&config).wrap_err("failed to serialize user config")?;
@@ -267,39 +607,118 @@ impl UserConfig {
 
         Ok(config)
     }
+
+    /// Returns the `TypeSettings` for the given `PluginGroupType`
+    pub fn type_settings(&self, group_type: &PluginGroupType) -> &TypeSettings {
+        match group_type {
+            PluginGroupType::Effect => &self.effect,
+            PluginGroupType::Generator => &self.generator,
+        }
+    }
+}
+
+/// A problem found when loading the `Config` that did not prevent it from loading
+#[derive(Debug)]
+pub enum ConfigWarning {
+    /// A plugin group is sorted into the same folder as a previously loaded plugin group
+    DuplicateGroup { folder: PathBuf, path: PathBuf },
+    /// A plugin group file failed to load, so it was skipped
+    InvalidGroup(GroupFileError),
+}
+
+/// An error that occurred when loading a plugin group file, along with
+/// where it occurred in the file if the file failed to parse
+#[derive(Debug)]
+pub struct GroupFileError {
+    pub path: PathBuf,
+    /// The line the error occurred on, starting from 1
+    pub line: Option<usize>,
+    /// The column the error occurred at, starting from 1
+    pub column: Option<usize>,
+    pub error: Report,
+}
+
+impl GroupFileError {
+    fn new(path: PathBuf, error: Report) -> Self {
+        let line_col = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<toml::de::Error>())
+            .and_then(toml::de::Error::line_col);
+
+        Self {
+            path,
+            line: line_col.map(|(line, _)| line + 1),
+            column: line_col.map(|(_, column)| column + 1),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for GroupFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        let mut location = String::new();
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{line}:{column}")?;
+            location = format!(" at line {line} column {column}");
+        }
+
+        // The first message only names the file, and the location is already displayed
+        let causes: Vec<String> = self
+            .error
+            .chain()
+            .skip(1)
+            .map(|cause| {
+                let cause = cause.to_string();
+                cause.strip_suffix(&location).unwrap_or(&cause).to_owned()
+            })
+            .collect();
+        if causes.is_empty() {
+            write!(f, ": {}", self.error)
+        } else {
+            write!(f, ": {}", causes.join(": "))
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Config {
     pub user: UserConfig,
-    pub plugin_database: PluginDatabase,
+    plugin_database: OnceCell<PluginDatabase>,
     pub effects: PluginGroupData,
     pub generators: PluginGroupData,
+    pub state_path: PathBuf,
+    pub warnings: Vec<ConfigWarning>,
 }
 
 impl Config {
     fn new(
         user_config: UserConfig,
-        plugin_database: PluginDatabase,
         effects: PluginGroupData,
         generators: PluginGroupData,
+        state_path: &Path,
+        warnings: Vec<ConfigWarning>,
     ) -> Self {
         Self {
             user: user_config,
-            plugin_database,
+            plugin_database: OnceCell::new(),
             effects,
             generators,
+            state_path: state_path.to_owned(),
+            warnings,
         }
     }
 
     /// Creates a new `Config` from the given [`Path`].
-    pub fn from_file(config_path: &Path) -> Result<Self> {
+    ///
+    /// If `strict` is false, plugin group files that fail to load are skipped
+    /// and recorded as a [`ConfigWarning::InvalidGroup`] instead of returning an error.
+    pub fn from_file(config_path: &Path, strict: bool) -> Result<Self> {
         // Create config directories if they don't exist
         Self::create_directory(config_path)?;
 
-        // Create user config and plugin database
+        // Create user config, the plugin database is only loaded when it is first used
         let user_config = UserConfig::new(config_path)?;
-        let plugin_database = PluginDatabase::new(&user_config.plugin_database_path)?;
 
         // Get directories containing plugin group definitions and create them if they don't exist
         let effects_dir = PluginGroupType::Effect.path(config_path);
@@ -308,29 +727,116 @@ impl Config {
         Self::create_directory(&generators_dir)?;
 
         // Get plugin groups
+        let mut warnings = Vec::new();
         let effects = PluginGroupData::new(
             PluginGroupType::Effect,
             &effects_dir,
-            Self::groups(&effects_dir)?,
+            Self::groups(&effects_dir, strict, &mut warnings)?,
         );
 
         let generators = PluginGroupData::new(
             PluginGroupType::Generator,
             &generators_dir,
-            Self::groups(&generators_dir)?,
+            Self::groups(&generators_dir, strict, &mut warnings)?,
         );
 
-        Ok(Self::new(user_config, plugin_database, effects, generators))
+        Ok(Self::new(
+            user_config,
+            effects,
+            generators,
+            &config_path.join("state.toml"),
+            warnings,
+        ))
     }
 
-    /// Creates a [`Vec`] of any `PluginGroup`s found in the given [`Path`]
-    /// by deserializing any TOML files in the directory.
-    fn groups(path: &Path) -> Result<Vec<PluginGroup>> {
+    /// Returns the `PluginDatabase`, loading it the first time it is used.
+    ///
+    /// # Errors
+    /// The function will return an error if the plugin database
+    /// pointed to by the user config is invalid.
+    pub fn plugin_database(&self) -> Result<&PluginDatabase> {
+        if let Some(plugin_database) = self.plugin_database.get() {
+            return Ok(plugin_database);
+        }
+
+        let plugin_database =
+            PluginDatabase::new(&self.user.plugin_database_path, &self.user.prefer).wrap_err_with(
+                || {
+                    eyre!(
+                        "failed to load plugin database at {}",
+                        self.user.plugin_database_path.display()
+                    )
+                },
+            )?;
+        Ok(self.plugin_database.get_or_init(|| plugin_database))
+    }
+
+    /// Creates a [`Vec`] of any `PluginGroup`s found in the given [`Path`] and its
+    /// subfolders by deserializing any TOML files.
+    ///
+    /// Groups in a subfolder are sorted into the matching subfolder of
+    /// the plugin database, e.g. `effect/Mixing/eq.toml` is sorted into `Effects/Mixing/`.
+    /// Any problems that don't prevent the groups from loading are added to `warnings`.
+    fn groups(
+        path: &Path,
+        strict: bool,
+        warnings: &mut Vec<ConfigWarning>,
+    ) -> Result<Vec<PluginGroup>> {
+        let mut files = Vec::new();
+        Self::group_files(path, &mut files)?;
+        files.sort();
+
         let mut groups = Vec::new();
-        let mut group_names = HashSet::<String>::new();
+        let mut group_folders = HashSet::<PathBuf>::new();
+        for file in files {
+            let group = match Self::group(path, &file) {
+                Ok(group) => group,
+                Err(error) if !strict => {
+                    warnings.push(ConfigWarning::InvalidGroup(GroupFileError::new(
+                        file, error,
+                    )));
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+            let folder = group.folder();
+            if group_folders.contains(&folder) {
+                warnings.push(ConfigWarning::DuplicateGroup { folder, path: file });
+            } else {
+                group_folders.insert(folder);
+            }
+
+            groups.push(group);
+        }
+
+        Ok(groups)
+    }
+
+    /// Loads the plugin group in `file`, which is inside the plugin group folder at `path`
+    fn group(path: &Path, file: &Path) -> Result<PluginGroup> {
+        let mut group = PluginGroup::from_file(file)?;
+        if let Some(directory) = file.parent().and_then(|dir| dir.strip_prefix(path).ok()) {
+            for folder in directory.iter() {
+                validate_name(&folder.to_string_lossy()).wrap_err_with(|| {
+                    eyre!("invalid plugin group folder {}", directory.display())
+                })?;
+            }
+
+            group.directory = directory.to_owned();
+        }
+
+        Ok(group)
+    }
+
+    /// Adds the path of every TOML file in the given folder and its subfolders to `files`
+    fn group_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
         for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let path = entry.path();
+            let path = entry?.path();
+            if path.is_dir() {
+                Self::group_files(&path, files)?;
+                continue;
+            }
 
             // Ensure path is a file and extension is correct before attempting to deserialize
             let extension = path.extension();
@@ -338,27 +844,10 @@ impl Config {
                 continue;
             }
 
-            let group = PluginGroup::from_file(&path)?;
-            if group_names.contains(&group.name) {
-                println!(
-                    "{}{}{}{}",
-                    "WARN: A plugin group with the name '".yellow(),
-                    group.name.blue(),
-                    "' already exists. Overwriting with the group defined in ".yellow(),
-                    path.file_name()
-                        .ok_or_else(|| eyre!("failed to unwrap OsStr for {}", path.display()))?
-                        .to_str()
-                        .ok_or_else(|| eyre!("failed to unwrap str for {}", path.display()))?
-                        .blue()
-                );
-            } else {
-                group_names.insert(group.name.to_owned());
-            }
-
-            groups.push(group);
+            files.push(path);
         }
 
-        Ok(groups)
+        Ok(())
     }
 
     /// Creates a directory, **including parents**, from the given [`Path`].
@@ -372,3 +861,43 @@ impl Config {
         fs::create_dir_all(path).wrap_err(format!("failed to create {}", path.display()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugin_group_round_trips() {
+        let group = PluginGroup {
+            path: Some("Mixing/EQ".to_owned()),
+            include: Some(PluginMatcher {
+                glob: vec!["FabFilter *".to_owned()],
+                regex: vec![r"^Pro-Q \d$".to_owned()],
+                contains: vec!["eq".to_owned()],
+            }),
+            exclude: Some(PluginMatcher {
+                contains: vec!["stereo".to_owned()],
+                ..Default::default()
+            }),
+            prefer: Some(vec!["vst3".to_owned(), "clap".to_owned()]),
+            both: Some(true),
+            priority: Some(-2),
+            ..PluginGroup::new("EQ", vec!["FabFilter Pro-Q 3".to_owned()])
+        };
+
+        let contents = toml::to_string(&group).unwrap();
+        let parsed: PluginGroup = toml::from_str(&contents).unwrap();
+        assert_eq!(parsed.name, group.name);
+        assert_eq!(parsed.path, group.path);
+        assert_eq!(parsed.plugins, group.plugins);
+        assert_eq!(parsed.prefer, group.prefer);
+        assert_eq!(parsed.both, group.both);
+        assert_eq!(parsed.priority, group.priority);
+
+        let include = parsed.include.unwrap();
+        assert_eq!(include.glob, ["FabFilter *"]);
+        assert_eq!(include.regex, [r"^Pro-Q \d$"]);
+        assert_eq!(include.contains, ["eq"]);
+        assert_eq!(parsed.exclude.unwrap().contains, ["stereo"]);
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use serde::Serialize;
+
+use crate::config::{Config, PluginGroupData};
+
+/// A plugin that is in a plugin group, but isn't installed
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingPlugin {
+    pub plugin: String,
+    /// The folder of the plugin group, relative to the plugin database folder of its type
+    pub group: PathBuf,
+}
+
+/// A plugin that is in more than one plugin group
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicatePlugin {
+    pub plugin: String,
+    /// The folders of the plugin groups, relative to the plugin database folder of their type
+    pub groups: Vec<PathBuf>,
+}
+
+/// How the installed plugins of a single `PluginGroupType` are covered by its plugin groups
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeCoverage {
+    pub group_type: String,
+    /// The number of installed plugins
+    pub installed: usize,
+    /// The number of installed plugins that are in at least one plugin group
+    pub grouped: usize,
+    /// The installed plugins that aren't in any plugin group
+    pub ungrouped: Vec<String>,
+    /// The plugins in plugin groups that aren't installed
+    pub not_installed: Vec<MissingPlugin>,
+    /// The plugins that are in more than one plugin group
+    pub duplicates: Vec<DuplicatePlugin>,
+}
+
+impl TypeCoverage {
+    /// Compares the installed plugins of a `PluginGroupType` against its plugin groups.
+    ///
+    /// Plugin groups are identified by their folder, since groups with the
+    /// same name can be sorted into different folders.
+    fn new(group_data: &PluginGroupData, installed_names: &[String]) -> Result<Self> {
+        let mut plugin_groups: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for group in &group_data.groups {
+            let plugins = group
+                .resolve_plugins(installed_names)
+                .wrap_err_with(|| eyre!("failed to resolve plugins of '{}'", group.name))?;
+
+            let folder = group.folder();
+            for plugin in plugins {
+                let groups = plugin_groups.entry(plugin).or_default();
+                if !groups.contains(&folder) {
+                    groups.push(folder.to_owned());
+                }
+            }
+        }
+
+        let installed: BTreeSet<&String> = installed_names.iter().collect();
+        let ungrouped = installed_names
+            .iter()
+            .filter(|name| !plugin_groups.contains_key(*name))
+            .cloned()
+            .collect();
+
+        let mut not_installed = Vec::new();
+        let mut duplicates = Vec::new();
+        for (plugin, groups) in &plugin_groups {
+            if !installed.contains(plugin) {
+                not_installed.extend(groups.iter().map(|group| MissingPlugin {
+                    plugin: plugin.to_owned(),
+                    group: group.to_owned(),
+                }));
+            }
+
+            if groups.len() > 1 {
+                duplicates.push(DuplicatePlugin {
+                    plugin: plugin.to_owned(),
+                    groups: groups.to_owned(),
+                });
+            }
+        }
+
+        Ok(Self {
+            group_type: group_data.group_type.name(),
+            installed: installed_names.len(),
+            grouped: plugin_groups
+                .keys()
+                .filter(|plugin| installed.contains(plugin))
+                .count(),
+            ungrouped,
+            not_installed,
+            duplicates,
+        })
+    }
+}
+
+/// Compares the installed plugins of each `PluginGroupType` against its plugin groups.
+///
+/// Plugins added to a plugin group by its `include` rules are treated as grouped.
+pub fn coverage(config: &Config) -> Result<Vec<TypeCoverage>> {
+    let plugin_database = config.plugin_database()?;
+    [&config.effects, &config.generators]
+        .into_iter()
+        .map(|group_data| {
+            let installed_names = plugin_database
+                .get_group(&group_data.group_type)
+                .installed
+                .plugin_names()?;
+            TypeCoverage::new(group_data, &installed_names)
+        })
+        .collect()
+}
@@ -0,0 +1,297 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use color_eyre::Result;
+use serde::Serialize;
+
+use crate::{
+    config::{Config, ConfigWarning, Placement},
+    plan::{Action, Plan, Skipped, SortOptions},
+    state::{hash_file, SortState},
+};
+
+/// How serious a problem found by [`diagnose`] is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Returns the name of the severity
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// The part of the setup a problem found by [`diagnose`] belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Plugin group files
+    Config,
+    /// The plugin database pointed to by the user config
+    Database,
+    /// Plugins listed in plugin groups
+    Plugins,
+    /// Files placed into the plugin database when sorting
+    SortedFiles,
+    /// Folders created in the plugin database when sorting
+    Folders,
+}
+
+impl Category {
+    /// Returns the name of the category
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Config => "Config",
+            Self::Database => "Plugin database",
+            Self::Plugins => "Plugins",
+            Self::SortedFiles => "Sorted files",
+            Self::Folders => "Folders",
+        }
+    }
+}
+
+/// A single problem found by [`diagnose`]
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    pub severity: Severity,
+    pub category: Category,
+    pub message: String,
+    /// The action that repairs the problem, if it can be repaired safely
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Action>,
+}
+
+impl Problem {
+    fn new(severity: Severity, category: Category, message: String) -> Self {
+        Self {
+            severity,
+            category,
+            message,
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: Action) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// Every problem found by [`diagnose`], ordered by category
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    /// Returns the number of problems with the given `Severity`
+    pub fn count(&self, severity: Severity) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.severity == severity)
+            .count()
+    }
+
+    /// Creates a `Plan` containing the fix of every problem that can be repaired
+    pub fn fix_plan(&self) -> Plan {
+        Plan {
+            actions: self
+                .problems
+                .iter()
+                .filter_map(|problem| problem.fix.clone())
+                .collect(),
+            skipped: Vec::new(),
+        }
+    }
+}
+
+/// Checks the plugin groups, plugin database and sorted files of the `Config` for problems.
+///
+/// Plugin group files that failed to load and a plugin database that can't be loaded
+/// are errors. Anything that stops plugins from being sorted as expected is a warning.
+pub fn diagnose(config: &Config) -> Result<Report> {
+    let mut report = Report::default();
+    check_config(config, &mut report);
+
+    let plugin_database = match config.plugin_database() {
+        Ok(plugin_database) => plugin_database,
+        Err(err) => {
+            report.problems.push(Problem::new(
+                Severity::Error,
+                Category::Database,
+                format!("failed to load the plugin database: {err:#}"),
+            ));
+            return Ok(report);
+        }
+    };
+
+    let state = SortState::from_file(&config.state_path)?;
+    let options = SortOptions {
+        placement: config.user.placement,
+        both: config.user.both,
+        mirror: false,
+    };
+    for group_data in [&config.effects, &config.generators] {
+        let settings = config.user.type_settings(&group_data.group_type);
+        let (plan, _) = Plan::sort(plugin_database, group_data, options, settings, &state)?;
+        check_skipped(&plan.skipped, &mut report);
+    }
+
+    check_sorted_files(&state, &mut report)?;
+    check_folders(&state, &mut report)?;
+
+    report.problems.sort_by_key(|problem| problem.category);
+    Ok(report)
+}
+
+fn check_config(config: &Config, report: &mut Report) {
+    for warning in &config.warnings {
+        report.problems.push(match warning {
+            ConfigWarning::DuplicateGroup { folder, path } => Problem::new(
+                Severity::Warning,
+                Category::Config,
+                format!(
+                    "{} is sorted into {}, which is already used by another plugin group",
+                    path.display(),
+                    folder.display()
+                ),
+            ),
+            ConfigWarning::InvalidGroup(error) => Problem::new(
+                Severity::Error,
+                Category::Config,
+                format!("failed to load {error}"),
+            ),
+        });
+    }
+}
+
+fn check_skipped(skipped: &[Skipped], report: &mut Report) {
+    for skipped in skipped {
+        let problem = match skipped {
+            Skipped::EmptyGroup { group } => Problem::new(
+                Severity::Info,
+                Category::Plugins,
+                format!("'{group}' contains no plugins"),
+            ),
+            Skipped::NotInstalled {
+                group,
+                plugin,
+                suggestions,
+            } => {
+                let mut message = format!("'{plugin}' in '{group}' is not installed");
+                if !suggestions.is_empty() {
+                    message.push_str(&format!(", did you mean '{}'?", suggestions.join("', '")));
+                }
+                Problem::new(Severity::Warning, Category::Plugins, message)
+            }
+            Skipped::Conflict {
+                group,
+                plugin,
+                placed_in,
+            } => Problem::new(
+                Severity::Info,
+                Category::Plugins,
+                format!("'{plugin}' in '{group}' is only sorted into '{placed_in}'"),
+            ),
+            Skipped::Unmanaged { path } => Problem::new(
+                Severity::Warning,
+                Category::SortedFiles,
+                format!(
+                    "{} already exists and wasn't created when sorting, so it isn't replaced",
+                    path.display()
+                ),
+            ),
+            Skipped::Changed { .. } => continue,
+        };
+
+        report.problems.push(problem);
+    }
+}
+
+/// Finds sorted files whose installed plugin file has changed or been removed since sorting
+fn check_sorted_files(state: &SortState, report: &mut Report) -> Result<()> {
+    for file in &state.files {
+        if !file.exists() {
+            continue;
+        }
+
+        let Some(source) = &file.source else {
+            continue;
+        };
+
+        if !source.is_file() {
+            report.problems.push(Problem::new(
+                Severity::Warning,
+                Category::SortedFiles,
+                format!(
+                    "{} was sorted from {}, which no longer exists",
+                    file.path.display(),
+                    source.display()
+                ),
+            ));
+            continue;
+        }
+
+        // Symbolic links always point to the installed file, and changed files are left alone
+        if file.placement == Placement::Symlink || !file.is_unchanged()? {
+            continue;
+        }
+
+        if hash_file(source)? != hash_file(&file.path)? {
+            report.problems.push(
+                Problem::new(
+                    Severity::Warning,
+                    Category::SortedFiles,
+                    format!(
+                        "{} is outdated, {} has changed since it was sorted",
+                        file.path.display(),
+                        source.display()
+                    ),
+                )
+                .with_fix(Action::Overwrite {
+                    from: source.to_owned(),
+                    to: file.path.to_owned(),
+                    placement: file.placement,
+                }),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds folders created when sorting that are now empty, including
+/// folders that only contain other empty folders
+fn check_folders(state: &SortState, report: &mut Report) -> Result<()> {
+    let mut folders: Vec<&PathBuf> = state.folders.iter().filter(|f| f.is_dir()).collect();
+    folders.sort_by_key(|folder| std::cmp::Reverse(folder.components().count()));
+
+    let mut empty = HashSet::new();
+    for folder in folders {
+        if !Plan::will_be_empty(folder, &empty)? {
+            continue;
+        }
+
+        empty.insert(folder.to_owned());
+        report.problems.push(
+            Problem::new(
+                Severity::Warning,
+                Category::Folders,
+                format!("{} is empty", folder.display()),
+            )
+            .with_fix(Action::RemoveDir {
+                path: folder.to_owned(),
+            }),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,359 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+
+const HEADER_CHUNK: &[u8; 4] = b"FLhd";
+const DATA_CHUNK: &[u8; 4] = b"FLdt";
+
+/// Event IDs of the events that are read from the `FLdt` chunk
+const EVENT_VERSION: u8 = 199;
+const EVENT_PLUGIN_NAME: u8 = 203;
+const EVENT_PLUGIN_DATA: u8 = 213;
+
+/// Record IDs used inside the plugin data event written by the Fruity Wrapper
+const RECORD_FOURCC: u32 = 51;
+const RECORD_GUID: u32 = 52;
+const RECORD_NAME: u32 = 54;
+const RECORD_PATH: u32 = 55;
+const RECORD_VENDOR: u32 = 56;
+
+/// Represents the format of a plugin referenced by an `.fst` file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PluginFormat {
+    Vst2,
+    Vst3,
+    Clap,
+}
+
+impl PluginFormat {
+    /// Returns the name of the plugin format
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Vst2 => "VST2",
+            Self::Vst3 => "VST3",
+            Self::Clap => "CLAP",
+        }
+    }
+
+    /// Determines the plugin format from the extension of a plugin path
+    fn from_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "dll" | "vst" | "so" => Some(Self::Vst2),
+            "vst3" => Some(Self::Vst3),
+            "clap" => Some(Self::Clap),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PluginFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The unique ID of a plugin, as stored by the Fruity Wrapper.
+///
+/// VST2 plugins are identified by a four character code, while
+/// VST3 plugins are identified by a 16 byte GUID.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PluginId {
+    FourCc([u8; 4]),
+    Guid([u8; 16]),
+}
+
+impl fmt::Display for PluginId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FourCc(code) if code.iter().all(u8::is_ascii_graphic) => {
+                f.write_str(&String::from_utf8_lossy(code))
+            }
+            Self::FourCc(code) => write!(f, "{:08X}", u32::from_be_bytes(*code)),
+            Self::Guid(guid) => {
+                for byte in guid {
+                    write!(f, "{byte:02X}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A data structure containing the information stored in
+/// an `.fst` file from FL Studio's plugin database.
+///
+/// Any field that is not present in the file is [`None`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FstFile {
+    pub name: Option<String>,
+    pub vendor: Option<String>,
+    pub format: Option<PluginFormat>,
+    pub plugin_path: Option<PathBuf>,
+    pub unique_id: Option<PluginId>,
+}
+
+impl FstFile {
+    /// Creates a [`FstFile`] by reading and parsing the file at the given [`Path`].
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path).wrap_err_with(|| eyre!("failed to read {}", path.display()))?;
+        Self::parse(&bytes).wrap_err_with(|| eyre!("failed to parse {}", path.display()))
+    }
+
+    /// Creates a [`FstFile`] from the contents of an `.fst` file.
+    ///
+    /// # Errors
+    /// The function will return an error if the `FLhd` or `FLdt` chunks
+    /// are missing, or if an event extends past the end of the data.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(4)? != HEADER_CHUNK {
+            return Err(eyre!("missing FLhd header chunk"));
+        }
+
+        let header_len = reader.u32()? as usize;
+        reader.take(header_len)?;
+
+        if reader.take(4)? != DATA_CHUNK {
+            return Err(eyre!("missing FLdt data chunk"));
+        }
+
+        let data_len = reader.u32()? as usize;
+        let mut events = Reader::new(reader.take(data_len)?);
+
+        let mut fst = Self::default();
+        let mut utf16 = true;
+        let mut wrapper_name = None;
+        while !events.is_empty() {
+            let id = events.u8()?;
+            let data = match id {
+                0..=63 => events.take(1)?,
+                64..=127 => events.take(2)?,
+                128..=191 => events.take(4)?,
+                _ => {
+                    let len = events.varint()?;
+                    events.take(len)?
+                }
+            };
+
+            match id {
+                EVENT_VERSION => utf16 = is_utf16_version(&decode_utf8(data)),
+                EVENT_PLUGIN_NAME => {
+                    fst.name = Some(if utf16 {
+                        decode_utf16(data)
+                    } else {
+                        decode_utf8(data)
+                    })
+                }
+                EVENT_PLUGIN_DATA => wrapper_name = fst.read_plugin_data(data)?,
+                _ => {}
+            }
+        }
+
+        // The display name is not always stored, so fall back to the name of the wrapped plugin
+        if fst.name.as_deref().is_none_or(str::is_empty) {
+            fst.name = wrapper_name;
+        }
+
+        Ok(fst)
+    }
+
+    /// Reads the records stored in the plugin data event of the Fruity Wrapper,
+    /// returning the name of the wrapped plugin if one is stored.
+    fn read_plugin_data(&mut self, data: &[u8]) -> Result<Option<String>> {
+        let mut reader = Reader::new(data);
+
+        // Only versions 8 and 10 of the plugin data contain records
+        let kind = reader.u32()?;
+        if kind != 8 && kind != 10 {
+            return Ok(None);
+        }
+
+        let mut name = None;
+        while !reader.is_empty() {
+            let id = reader.u32()?;
+            let len = usize::try_from(reader.u64()?)
+                .map_err(|_| eyre!("plugin data record is too large"))?;
+            let record = reader.take(len)?;
+
+            match id {
+                RECORD_FOURCC => {
+                    if let Ok(code) = record.try_into() {
+                        self.unique_id = Some(PluginId::FourCc(code));
+                    }
+                }
+                RECORD_GUID => {
+                    if let Ok(guid) = record.try_into() {
+                        self.unique_id = Some(PluginId::Guid(guid));
+                    }
+                }
+                RECORD_NAME => name = Some(decode_utf8(record)),
+                RECORD_PATH => self.plugin_path = Some(PathBuf::from(decode_utf8(record))),
+                RECORD_VENDOR => self.vendor = Some(decode_utf8(record)),
+                _ => {}
+            }
+        }
+
+        self.format = self
+            .plugin_path
+            .as_ref()
+            .and_then(|path| PluginFormat::from_path(&path.to_string_lossy()))
+            .or(match self.unique_id {
+                Some(PluginId::FourCc(_)) => Some(PluginFormat::Vst2),
+                Some(PluginId::Guid(_)) => Some(PluginFormat::Vst3),
+                None => None,
+            });
+
+        Ok(name)
+    }
+}
+
+/// Determines if text events are stored as UTF-16 from an FL Studio
+/// version string. FL Studio 11.5 and later store text as UTF-16.
+fn is_utf16_version(version: &str) -> bool {
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse::<u32>().unwrap_or(0));
+    let major = parts.next().unwrap_or(0);
+    let minor = parts.next().unwrap_or(0);
+    (major, minor) >= (11, 5)
+}
+
+fn decode_utf8(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_end_matches('\0')
+        .to_owned()
+}
+
+fn decode_utf16(data: &[u8]) -> String {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+
+    String::from_utf16_lossy(&units)
+        .trim_end_matches('\0')
+        .to_owned()
+}
+
+/// A cursor over a byte slice that reads little endian values
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(eyre!("unexpected end of data"));
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    /// Reads the variable length size of a text or data event
+    fn varint(&mut self) -> Result<usize> {
+        let mut value = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7F) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(eyre!("event size is too large"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERUM_VST3: &[u8] = include_bytes!("../tests/fixtures/serum_vst3.fst");
+    const PRO_Q3_VST2: &[u8] = include_bytes!("../tests/fixtures/pro_q3_vst2.fst");
+    const SURGE_CLAP: &[u8] = include_bytes!("../tests/fixtures/surge_clap.fst");
+
+    #[test]
+    fn parses_vst3_plugin() {
+        let fst = FstFile::parse(SERUM_VST3).unwrap();
+        assert_eq!(fst.name.as_deref(), Some("Serum"));
+        assert_eq!(fst.vendor.as_deref(), Some("Xfer Records"));
+        assert_eq!(fst.format, Some(PluginFormat::Vst3));
+        assert_eq!(
+            fst.plugin_path,
+            Some(PathBuf::from(
+                r"C:\Program Files\Common Files\VST3\Serum.vst3"
+            ))
+        );
+        assert_eq!(
+            fst.unique_id.unwrap().to_string(),
+            "000102030405060708090A0B0C0D0E0F"
+        );
+    }
+
+    #[test]
+    fn parses_vst2_plugin() {
+        let fst = FstFile::parse(PRO_Q3_VST2).unwrap();
+        assert_eq!(fst.name.as_deref(), Some("FabFilter Pro-Q 3"));
+        assert_eq!(fst.vendor.as_deref(), Some("FabFilter"));
+        assert_eq!(fst.format, Some(PluginFormat::Vst2));
+        assert_eq!(fst.unique_id, Some(PluginId::FourCc(*b"FQ3p")));
+        assert_eq!(fst.unique_id.unwrap().to_string(), "FQ3p");
+    }
+
+    #[test]
+    fn parses_clap_plugin() {
+        let fst = FstFile::parse(SURGE_CLAP).unwrap();
+        assert_eq!(fst.name.as_deref(), Some("Surge XT"));
+        assert_eq!(fst.vendor.as_deref(), Some("Surge Synth Team"));
+        assert_eq!(fst.format, Some(PluginFormat::Clap));
+        assert_eq!(fst.unique_id, None);
+    }
+
+    #[test]
+    fn reads_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/surge_clap.fst");
+        let fst = FstFile::from_file(&path).unwrap();
+        assert_eq!(fst.name.as_deref(), Some("Surge XT"));
+        assert_eq!(fst.format, Some(PluginFormat::Clap));
+    }
+
+    #[test]
+    fn rejects_invalid_header() {
+        assert!(FstFile::parse(b"RIFF\x06\x00\x00\x00").is_err());
+        assert!(FstFile::parse(&SERUM_VST3[..4]).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        assert!(FstFile::parse(&SERUM_VST3[..SERUM_VST3.len() - 10]).is_err());
+    }
+}
@@ -0,0 +1,21 @@
+//! Sorts FL Studio plugin files (`.fst` files) into folders in its plugin database,
+//! using plugin group definitions.
+//!
+//! The `fls` command-line tool is built on top of this library,
+//! and is only built when the `cli` feature is enabled.
+
+pub mod config;
+pub mod coverage;
+pub mod doctor;
+pub mod fst;
+pub mod matcher;
+pub mod plan;
+pub mod plugin;
+pub mod sorter;
+pub mod state;
+pub mod suggest;
+pub mod validate;
+pub mod vendor;
+
+pub use config::{Config, PluginDatabase, PluginGroup};
+pub use sorter::{sort, unsort, SortResult, UnsortResult};
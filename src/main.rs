@@ -1,21 +1,25 @@
+use std::path::PathBuf;
+
 use clap::Parser;
-use color_eyre::{
-    eyre::{eyre, Context},
-    Result,
-};
+use color_eyre::{eyre::Context, Result};
 use commands::{RunnableCommand, Subcommand};
-use config::Config;
-use dirs::home_dir;
+use fl_plugin_sorter::{config::default_config_dir, Config};
 
 mod commands;
-mod config;
-mod plugin;
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
 struct Cli {
     #[clap(subcommand)]
     subcommand: Subcommand,
+
+    /// Path to the directory containing the config and plugin groups
+    #[arg(long, global = true, env = "FLS_CONFIG_DIR")]
+    config_dir: Option<PathBuf>,
+
+    /// Fail if any plugin group file fails to load, instead of skipping it
+    #[arg(long, global = true, action)]
+    strict: bool,
 }
 
 fn main() -> Result<()> {
@@ -24,16 +28,25 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Load config
-    let mut config_path = home_dir().ok_or_else(|| eyre!("failed to get home directory"))?;
-    if !config_path.exists() {
-        return Err(eyre!("home directory does not exist"));
-    }
+    let config_path = match cli.config_dir {
+        Some(config_dir) => config_dir,
+        None => default_config_dir()?,
+    };
 
-    config_path.push(".config/flsorter");
-    let config = Config::from_file(&config_path).wrap_err("failed to load config")?;
+    // The doctor reports invalid plugin groups itself, so it never fails to load them
+    let is_doctor = matches!(cli.subcommand, Subcommand::Doctor(_));
+    let config = Config::from_file(&config_path, cli.strict && !is_doctor)
+        .wrap_err("failed to load config")?;
+    if !is_doctor {
+        commands::display_config_warnings(&config);
+    }
 
     // Run subcommand
     match cli.subcommand {
+        Subcommand::Coverage(sub) => sub.run(&config),
+        Subcommand::Doctor(sub) => sub.run(&config),
+        Subcommand::Edit(sub) => sub.run(&config),
+        Subcommand::Fix(sub) => sub.run(&config),
         Subcommand::Generate(sub) => sub.run(&config),
         Subcommand::List(sub) => sub.run(&config),
         Subcommand::New(sub) => sub.run(&config),
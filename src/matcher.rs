@@ -0,0 +1,130 @@
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use glob::Pattern;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A set of rules that are used to match the names of installed plugins.
+///
+/// A plugin name matches if it matches **any** of the defined rules.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PluginMatcher {
+    /// Glob patterns, e.g. `Waves *`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub glob: Vec<String>,
+
+    /// Regular expressions, e.g. `^CLA-\d+`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regex: Vec<String>,
+
+    /// Case-insensitive substrings, e.g. `waves`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contains: Vec<String>,
+}
+
+impl PluginMatcher {
+    /// Compiles the rules into a [`CompiledMatcher`].
+    ///
+    /// # Errors
+    /// The function will return an error if any glob pattern or
+    /// regular expression is invalid.
+    pub fn compile(&self) -> Result<CompiledMatcher> {
+        let globs = self
+            .glob
+            .iter()
+            .map(|pattern| {
+                Pattern::new(pattern).wrap_err_with(|| eyre!("invalid glob pattern '{pattern}'"))
+            })
+            .collect::<Result<_>>()?;
+
+        let regexes = self
+            .regex
+            .iter()
+            .map(|regex| {
+                Regex::new(regex).wrap_err_with(|| eyre!("invalid regular expression '{regex}'"))
+            })
+            .collect::<Result<_>>()?;
+
+        let contains = self.contains.iter().map(|s| s.to_lowercase()).collect();
+
+        Ok(CompiledMatcher {
+            globs,
+            regexes,
+            contains,
+        })
+    }
+}
+
+/// A [`PluginMatcher`] with its patterns compiled, ready to match plugin names
+#[derive(Debug)]
+pub struct CompiledMatcher {
+    globs: Vec<Pattern>,
+    regexes: Vec<Regex>,
+    contains: Vec<String>,
+}
+
+impl CompiledMatcher {
+    /// Returns whether the plugin name matches any of the rules
+    pub fn is_match(&self, name: &str) -> bool {
+        let lowercase_name = name.to_lowercase();
+        self.globs.iter().any(|glob| glob.matches(name))
+            || self.regexes.iter().any(|regex| regex.is_match(name))
+            || self
+                .contains
+                .iter()
+                .any(|substring| lowercase_name.contains(substring))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(glob: &[&str], regex: &[&str], contains: &[&str]) -> PluginMatcher {
+        let to_strings = |rules: &[&str]| rules.iter().map(|rule| rule.to_string()).collect();
+        PluginMatcher {
+            glob: to_strings(glob),
+            regex: to_strings(regex),
+            contains: to_strings(contains),
+        }
+    }
+
+    #[test]
+    fn matches_any_rule() {
+        let compiled = matcher(&["Waves *"], &[r"^CLA-\d+$"], &["fabfilter"])
+            .compile()
+            .unwrap();
+
+        assert!(compiled.is_match("Waves H-Delay Stereo"));
+        assert!(compiled.is_match("CLA-76"));
+        assert!(compiled.is_match("FabFilter Pro-Q 3"));
+        assert!(!compiled.is_match("Serum"));
+        assert!(!compiled.is_match("CLA-76 Stereo"));
+    }
+
+    #[test]
+    fn only_contains_ignores_case() {
+        let compiled = matcher(&["Waves *"], &["^Serum$"], &["SURGE"])
+            .compile()
+            .unwrap();
+
+        assert!(compiled.is_match("surge xt"));
+        assert!(!compiled.is_match("waves CLA-76"));
+        assert!(!compiled.is_match("serum"));
+    }
+
+    #[test]
+    fn empty_matcher_matches_nothing() {
+        let compiled = PluginMatcher::default().compile().unwrap();
+        assert!(!compiled.is_match("Serum"));
+        assert!(!compiled.is_match(""));
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        assert!(matcher(&["Waves [*"], &[], &[]).compile().is_err());
+        assert!(matcher(&[], &["CLA-(76"], &[]).compile().is_err());
+    }
+}
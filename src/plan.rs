@@ -0,0 +1,883 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use serde::Serialize;
+
+use crate::{
+    config::{InstalledPlugins, Placement, PluginDatabase, PluginGroupData, TypeSettings},
+    state::{hash_file, SortState, SortedFile},
+    suggest::suggest,
+};
+
+/// The maximum number of installed plugins suggested for a plugin that isn't installed
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// A single change to the plugin database
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    CreateDir {
+        path: PathBuf,
+    },
+    Place {
+        from: PathBuf,
+        to: PathBuf,
+        placement: Placement,
+    },
+    Overwrite {
+        from: PathBuf,
+        to: PathBuf,
+        placement: Placement,
+    },
+    Delete {
+        path: PathBuf,
+    },
+    RemoveDir {
+        path: PathBuf,
+    },
+}
+
+/// The reason something was skipped when planning
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Skipped {
+    /// A plugin group contains no plugins
+    EmptyGroup { group: String },
+    /// A plugin in a plugin group is not installed, along with
+    /// the names of similar installed plugins
+    NotInstalled {
+        group: String,
+        plugin: String,
+        suggestions: Vec<String>,
+    },
+    /// A plugin in several plugin groups was only placed in the
+    /// group with the highest priority, because the type is exclusive
+    Conflict {
+        group: String,
+        plugin: String,
+        placed_in: String,
+    },
+    /// A sorted file was modified after it was sorted
+    Changed { path: PathBuf },
+    /// A file that wasn't created when sorting, and doesn't match the installed plugin file,
+    /// already exists where a plugin would be placed
+    Unmanaged { path: PathBuf },
+}
+
+/// Options that control how plugins are sorted
+#[derive(Debug, Clone, Copy)]
+pub struct SortOptions {
+    /// How plugin files are placed into plugin group folders
+    pub placement: Placement,
+    /// Whether every installed format of a plugin is sorted, unless overridden by a plugin group
+    pub both: bool,
+    /// Whether sorted files that are no longer defined by a plugin group are removed
+    pub mirror: bool,
+}
+
+/// A list of [`Action`]s to apply to the plugin database, along with
+/// anything that was skipped when creating them.
+///
+/// The same `Plan` is used for displaying a dry run and for
+/// making the changes, so the two can never disagree.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Plan {
+    pub actions: Vec<Action>,
+    pub skipped: Vec<Skipped>,
+}
+
+impl Plan {
+    /// Creates a `Plan` that sorts the plugins of the given `PluginGroupData`
+    /// into the plugin database.
+    ///
+    /// When every installed format of a plugin is sorted, and it is installed in
+    /// more than one format, each file is suffixed with its format, e.g. `Serum (VST3).fst`.
+    ///
+    /// If the `TypeSettings` define an `unsorted` folder, every installed
+    /// plugin that isn't in a plugin group is sorted into it. If they are `exclusive`,
+    /// a plugin in several plugin groups is only sorted into the group with the highest
+    /// priority, or the first of those groups if their priorities are equal.
+    ///
+    /// Existing files are only overwritten if they are recorded in the `SortState`
+    /// and unchanged since they were sorted, so files created by FL Studio or the
+    /// user are never replaced. An unrecorded file with the same contents as the
+    /// installed plugin file it would be placed from is adopted instead, so files
+    /// sorted before the `SortState` existed are recorded again.
+    ///
+    /// Every folder defined by the plugin groups and the `unsorted` folder is also
+    /// returned along with their parents, even if nothing is placed in them,
+    /// so they can be kept when mirroring.
+    pub fn sort(
+        plugin_database: &PluginDatabase,
+        group_data: &PluginGroupData,
+        options: SortOptions,
+        settings: &TypeSettings,
+        state: &SortState,
+    ) -> Result<(Self, HashSet<PathBuf>)> {
+        let mut plan = Self::default();
+        let mut folders = HashSet::new();
+        let database_group = plugin_database.get_group(&group_data.group_type);
+        let installed_plugins = &database_group.installed;
+
+        // Installed plugin names are used to resolve rule-based groups, to suggest
+        // names for plugins that aren't installed, and to find unsorted plugins
+        let installed_names = installed_plugins.plugin_names()?;
+
+        let mut resolved_groups = Vec::with_capacity(group_data.groups.len());
+        for group in &group_data.groups {
+            let plugins = group
+                .resolve_plugins(&installed_names)
+                .wrap_err_with(|| eyre!("failed to resolve plugins of '{}'", group.name))?;
+            resolved_groups.push((group, plugins));
+        }
+
+        // Maps each plugin to the index of the group it is placed in
+        let mut owners: HashMap<&str, usize> = HashMap::new();
+        if settings.exclusive {
+            for (index, (group, plugins)) in resolved_groups.iter().enumerate() {
+                for plugin_name in plugins {
+                    let owner = owners.entry(plugin_name).or_insert(index);
+                    let owner_priority = resolved_groups[*owner].0.priority.unwrap_or(0);
+                    if group.priority.unwrap_or(0) > owner_priority {
+                        *owner = index;
+                    }
+                }
+            }
+        }
+
+        let mut created_dirs = HashSet::new();
+        let mut claimed = HashSet::new();
+        for (index, (group, plugins)) in resolved_groups.iter().enumerate() {
+            let group_dir = plugin_database.get_group_path(group, &group_data.group_type);
+            folders.extend(group_dir.ancestors().map(Path::to_path_buf));
+            if plugins.is_empty() {
+                plan.skipped.push(Skipped::EmptyGroup {
+                    group: group.name.to_owned(),
+                });
+                continue;
+            }
+
+            let mut owned_plugins = Vec::with_capacity(plugins.len());
+            for plugin_name in plugins {
+                match owners.get(plugin_name.as_str()) {
+                    Some(&owner) if owner != index => plan.skipped.push(Skipped::Conflict {
+                        group: group.name.to_owned(),
+                        plugin: plugin_name.to_owned(),
+                        placed_in: resolved_groups[owner].0.name.to_owned(),
+                    }),
+                    _ => owned_plugins.push(plugin_name),
+                }
+            }
+
+            // Don't create a folder for a group whose plugins are all placed in other groups
+            if owned_plugins.is_empty() {
+                continue;
+            }
+
+            plan.create_dirs(&group_dir, &mut created_dirs);
+
+            let both = group.both.unwrap_or(options.both);
+            for plugin_name in owned_plugins {
+                let placed = plan.place_plugin(
+                    installed_plugins,
+                    plugin_name,
+                    &group_dir,
+                    group.prefer.as_deref(),
+                    both,
+                    options.placement,
+                );
+
+                if !placed {
+                    let suggestions = suggest(plugin_name, &installed_names, MAX_SUGGESTIONS)
+                        .into_iter()
+                        .map(str::to_owned)
+                        .collect();
+                    plan.skipped.push(Skipped::NotInstalled {
+                        group: group.name.to_owned(),
+                        plugin: plugin_name.to_owned(),
+                        suggestions,
+                    });
+                }
+
+                claimed.insert(plugin_name.as_str());
+            }
+        }
+
+        if let Some(unsorted) = settings.unsorted_folder() {
+            let unsorted_dir = database_group.folder.join(unsorted);
+            folders.extend(unsorted_dir.ancestors().map(Path::to_path_buf));
+            let unclaimed: Vec<&String> = installed_names
+                .iter()
+                .filter(|name| !claimed.contains(name.as_str()))
+                .collect();
+
+            if !unclaimed.is_empty() {
+                plan.create_dirs(&unsorted_dir, &mut created_dirs);
+            }
+
+            for plugin_name in unclaimed {
+                plan.place_plugin(
+                    installed_plugins,
+                    plugin_name,
+                    &unsorted_dir,
+                    None,
+                    options.both,
+                    options.placement,
+                );
+            }
+        }
+
+        plan.skip_unmanaged(state)?;
+        Ok((plan, folders))
+    }
+
+    /// Adds a [`Action::CreateDir`] for a folder and each of its missing parents,
+    /// unless they are already in `created_dirs`.
+    ///
+    /// Each folder is created separately, so nested folders can be removed when unsorting.
+    fn create_dirs(&mut self, dir: &Path, created_dirs: &mut HashSet<PathBuf>) {
+        let mut missing_dirs: Vec<&Path> =
+            dir.ancestors().take_while(|dir| !dir.exists()).collect();
+        missing_dirs.reverse();
+        for dir in missing_dirs {
+            if created_dirs.insert(dir.to_owned()) {
+                self.actions.push(Action::CreateDir {
+                    path: dir.to_owned(),
+                });
+            }
+        }
+    }
+
+    /// Adds the actions that place an installed plugin into a folder,
+    /// returning false if the plugin isn't installed.
+    ///
+    /// If `both` is true, every installed format of the plugin is placed,
+    /// otherwise only the first format in `prefer` is placed.
+    fn place_plugin(
+        &mut self,
+        installed_plugins: &InstalledPlugins,
+        plugin_name: &str,
+        dir: &Path,
+        prefer: Option<&[String]>,
+        both: bool,
+        placement: Placement,
+    ) -> bool {
+        let mut formats = installed_plugins.get_plugin_formats(plugin_name, prefer);
+        if formats.is_empty() {
+            return false;
+        }
+
+        if !both {
+            formats.truncate(1);
+        }
+
+        let suffix_format = formats.len() > 1;
+        for (folder, from) in formats {
+            let file_name = if suffix_format {
+                format!("{plugin_name} ({}).fst", folder.format().to_uppercase())
+            } else {
+                format!("{plugin_name}.fst")
+            };
+
+            let to = dir.join(file_name);
+            self.actions.push(if fs::symlink_metadata(&to).is_ok() {
+                Action::Overwrite {
+                    from,
+                    to,
+                    placement,
+                }
+            } else {
+                Action::Place {
+                    from,
+                    to,
+                    placement,
+                }
+            });
+        }
+
+        true
+    }
+
+    /// Replaces every [`Action::Overwrite`] of a file that isn't recorded in the
+    /// `SortState`, or that has changed since it was sorted, with a [`Skipped`] entry.
+    ///
+    /// Unrecorded files that match their installed plugin file are still overwritten,
+    /// which records them in the `SortState`.
+    fn skip_unmanaged(&mut self, state: &SortState) -> Result<()> {
+        for action in std::mem::take(&mut self.actions) {
+            let Action::Overwrite { from, to, .. } = &action else {
+                self.actions.push(action);
+                continue;
+            };
+
+            match state.files.iter().find(|file| &file.path == to) {
+                Some(file) if file.is_unchanged()? => self.actions.push(action),
+                Some(_) => self.skipped.push(Skipped::Changed {
+                    path: to.to_owned(),
+                }),
+                None if Self::matches_source(from, to) => self.actions.push(action),
+                None => self.skipped.push(Skipped::Unmanaged {
+                    path: to.to_owned(),
+                }),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the existing file at `to` has the same contents as the
+    /// installed plugin file at `from`, following symbolic links
+    fn matches_source(from: &Path, to: &Path) -> bool {
+        match (hash_file(from), hash_file(to)) {
+            (Ok(source), Ok(existing)) => source == existing,
+            _ => false,
+        }
+    }
+
+    /// Creates a `Plan` that removes the files and folders recorded in the `SortState`.
+    ///
+    /// Files are only removed if their contents are unchanged since they were sorted.
+    /// Folders are only removed if they will be empty, starting from the deepest folder.
+    pub fn unsort(state: &SortState) -> Result<Self> {
+        Self::remove_recorded(state, &HashSet::new())
+    }
+
+    /// Creates a `Plan` that removes the files and folders recorded in the `SortState`
+    /// that are no longer placed by the given sort actions, or in one of the `folders`
+    /// returned by [`Plan::sort`].
+    ///
+    /// Only files and folders created by sorting are removed, so anything
+    /// created by FL Studio or the user is left untouched.
+    pub fn mirror<'a>(
+        state: &SortState,
+        folders: &HashSet<PathBuf>,
+        sort_actions: impl IntoIterator<Item = &'a Action>,
+    ) -> Result<Self> {
+        let mut keep = folders.clone();
+        for action in sort_actions {
+            if let Action::Place { to, .. } | Action::Overwrite { to, .. } = action {
+                keep.insert(to.to_owned());
+            }
+        }
+
+        Self::remove_recorded(state, &keep)
+    }
+
+    /// Creates a `Plan` that removes the files and folders recorded in the `SortState`,
+    /// except for any paths in `keep`.
+    fn remove_recorded(state: &SortState, keep: &HashSet<PathBuf>) -> Result<Self> {
+        let mut plan = Self::default();
+        let mut removed = HashSet::new();
+        for file in &state.files {
+            if keep.contains(&file.path) || !file.exists() {
+                continue;
+            }
+
+            if !file.is_unchanged()? {
+                plan.skipped.push(Skipped::Changed {
+                    path: file.path.to_owned(),
+                });
+                continue;
+            }
+
+            removed.insert(file.path.to_owned());
+            plan.actions.push(Action::Delete {
+                path: file.path.to_owned(),
+            });
+        }
+
+        // Remove folders from the bottom up, so parents are empty by the time they are reached
+        let mut folders: Vec<&PathBuf> = state.folders.iter().collect();
+        folders.sort_by_key(|folder| std::cmp::Reverse(folder.components().count()));
+        for folder in folders {
+            if keep.contains(folder) || !folder.is_dir() || !Self::will_be_empty(folder, &removed)?
+            {
+                continue;
+            }
+
+            removed.insert(folder.to_owned());
+            plan.actions.push(Action::RemoveDir {
+                path: folder.to_owned(),
+            });
+        }
+
+        Ok(plan)
+    }
+
+    /// Returns whether every entry in a folder is going to be removed
+    pub(crate) fn will_be_empty(folder: &Path, removed: &HashSet<PathBuf>) -> Result<bool> {
+        for entry in fs::read_dir(folder)? {
+            if !removed.contains(&entry?.path()) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Adds the actions and skipped entries of another `Plan` to this one
+    pub fn append(&mut self, mut other: Plan) {
+        self.actions.append(&mut other.actions);
+        self.skipped.append(&mut other.skipped);
+    }
+
+    /// Returns the number of plugin files placed into the plugin database
+    pub fn placed_count(&self) -> usize {
+        self.actions
+            .iter()
+            .filter(|action| matches!(action, Action::Place { .. } | Action::Overwrite { .. }))
+            .count()
+    }
+
+    /// Returns the number of plugin files placed directly into the given folder
+    pub fn placed_count_in(&self, dir: &Path) -> usize {
+        self.actions
+            .iter()
+            .filter(|action| match action {
+                Action::Place { to, .. } | Action::Overwrite { to, .. } => to.parent() == Some(dir),
+                _ => false,
+            })
+            .count()
+    }
+
+    /// Returns the number of plugin files deleted from the plugin database
+    pub fn deleted_count(&self) -> usize {
+        self.actions
+            .iter()
+            .filter(|action| matches!(action, Action::Delete { .. }))
+            .count()
+    }
+
+    /// Returns the number of folders that plugin files are placed into
+    pub fn folder_count(&self) -> usize {
+        self.actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Place { to, .. } | Action::Overwrite { to, .. } => to.parent(),
+                _ => None,
+            })
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Applies every action to the plugin database, recording
+    /// the changes in the `SortState`.
+    ///
+    /// Entries that no longer exist are removed from the `SortState`,
+    /// even if an action fails.
+    pub fn execute(&self, state: &mut SortState) -> Result<()> {
+        let result = self.apply(state);
+        state.prune();
+        result
+    }
+
+    fn apply(&self, state: &mut SortState) -> Result<()> {
+        for action in &self.actions {
+            match action {
+                Action::CreateDir { path } => {
+                    fs::create_dir_all(path)
+                        .wrap_err_with(|| eyre!("failed to create {}", path.display()))?;
+                    state.add_folder(path);
+                }
+                Action::Place {
+                    from,
+                    to,
+                    placement,
+                }
+                | Action::Overwrite {
+                    from,
+                    to,
+                    placement,
+                } => {
+                    placement.place(from, to)?;
+                    state.add_file(SortedFile::new(to, from, *placement)?);
+                }
+                Action::Delete { path } => {
+                    fs::remove_file(path)
+                        .wrap_err_with(|| eyre!("failed to remove {}", path.display()))?;
+                }
+                Action::RemoveDir { path } => {
+                    fs::remove_dir(path)
+                        .wrap_err_with(|| eyre!("failed to remove {}", path.display()))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::config::{
+        InstalledFolder, PluginDatabaseGroup, PluginGroup, PluginGroupData, PluginGroupType,
+    };
+
+    const OPTIONS: SortOptions = SortOptions {
+        placement: Placement::Copy,
+        both: false,
+        mirror: true,
+    };
+
+    /// Creates a plugin database with the given effects installed as VST3 plugins
+    fn create_database(root: &Path, installed: &[&str]) -> PluginDatabase {
+        let installed_dir = root.join("Installed/Effects/VST3");
+        fs::create_dir_all(root.join("Effects")).unwrap();
+        for plugin in installed {
+            create_file(&installed_dir.join(format!("{plugin}.fst")), plugin);
+        }
+
+        let database_group = |group_type, folder: &str, installed_folders| PluginDatabaseGroup {
+            group_type,
+            installed: InstalledPlugins {
+                folders: installed_folders,
+            },
+            folder: root.join(folder),
+        };
+        PluginDatabase {
+            effects: database_group(
+                PluginGroupType::Effect,
+                "Effects",
+                vec![InstalledFolder {
+                    name: "VST3".to_owned(),
+                    path: installed_dir,
+                }],
+            ),
+            generators: database_group(PluginGroupType::Generator, "Generators", Vec::new()),
+        }
+    }
+
+    /// Creates the `PluginGroupData` of effect plugin groups
+    fn effect_groups(groups: Vec<PluginGroup>) -> PluginGroupData {
+        PluginGroupData {
+            group_type: PluginGroupType::Effect,
+            config_path: PathBuf::new(),
+            groups,
+        }
+    }
+
+    /// Creates a file containing `contents`, along with any missing parent folders
+    fn create_file(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Records a file in the `SortState` as if it was copied when sorting
+    fn record_file(state: &mut SortState, path: &Path) {
+        let source = Path::new("Installed/Effects/VST3/Plugin.fst");
+        state.add_file(SortedFile::new(path, source, Placement::Copy).unwrap());
+    }
+
+    /// Returns the paths deleted by the plan, sorted
+    fn deleted(plan: &Plan) -> Vec<&Path> {
+        let mut paths: Vec<&Path> = plan
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Delete { path } => Some(path.as_path()),
+                _ => None,
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Returns the folders removed by the plan, in the order they are removed
+    fn removed_dirs(plan: &Plan) -> Vec<&Path> {
+        plan.actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::RemoveDir { path } => Some(path.as_path()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unsort_skips_changed_files() {
+        let dir = TempDir::new().unwrap();
+        let group_dir = dir.path().join("EQ");
+        let unchanged = group_dir.join("FabFilter Pro-Q 3.fst");
+        let changed = group_dir.join("Waves CLA-76.fst");
+        create_file(&unchanged, "Pro-Q 3");
+        create_file(&changed, "CLA-76");
+
+        let mut state = SortState::default();
+        state.add_folder(&group_dir);
+        record_file(&mut state, &unchanged);
+        record_file(&mut state, &changed);
+        fs::write(&changed, "edited").unwrap();
+
+        let plan = Plan::unsort(&state).unwrap();
+        assert_eq!(deleted(&plan), [unchanged.as_path()]);
+        // The folder still contains the changed file
+        assert!(removed_dirs(&plan).is_empty());
+        assert!(matches!(
+            plan.skipped.as_slice(),
+            [Skipped::Changed { path }] if path == &changed
+        ));
+    }
+
+    #[test]
+    fn mirror_only_removes_unused_recorded_entries() {
+        let dir = TempDir::new().unwrap();
+        let mixing = dir.path().join("Mixing");
+        let eq = mixing.join("EQ");
+        let synths = dir.path().join("Synths");
+        let used = eq.join("FabFilter Pro-Q 3.fst");
+        let unused = eq.join("Waves CLA-76.fst");
+        let unused_group = synths.join("Serum.fst");
+        let unrecorded = eq.join("Mine.fst");
+        for file in [&used, &unused, &unused_group, &unrecorded] {
+            create_file(file, "plugin");
+        }
+
+        let mut state = SortState::default();
+        for folder in [&mixing, &eq, &synths] {
+            state.add_folder(folder);
+        }
+        for file in [&used, &unused, &unused_group] {
+            record_file(&mut state, file);
+        }
+
+        // The folders of the remaining group are kept, along with the file placed in it
+        let folders = HashSet::from([mixing.to_owned(), eq.to_owned()]);
+        let sort_actions = [Action::Overwrite {
+            from: PathBuf::from("Installed/Effects/VST3/FabFilter Pro-Q 3.fst"),
+            to: used.to_owned(),
+            placement: Placement::Copy,
+        }];
+
+        let plan = Plan::mirror(&state, &folders, &sort_actions).unwrap();
+        let mut expected = [unused.as_path(), unused_group.as_path()];
+        expected.sort();
+        assert_eq!(deleted(&plan), expected);
+        assert_eq!(removed_dirs(&plan), [synths.as_path()]);
+        assert!(plan.skipped.is_empty());
+    }
+
+    #[test]
+    fn unsort_removes_nested_folders_bottom_up() {
+        let dir = TempDir::new().unwrap();
+        let mixing = dir.path().join("Mixing");
+        let eq = mixing.join("EQ");
+        let linear = eq.join("Linear Phase");
+        let plugin = linear.join("FabFilter Pro-Q 3.fst");
+        create_file(&plugin, "Pro-Q 3");
+
+        // Folders are recorded from the top down, as they are created when sorting
+        let mut state = SortState::default();
+        for folder in [&mixing, &eq, &linear] {
+            state.add_folder(folder);
+        }
+        record_file(&mut state, &plugin);
+
+        let mut plan = Plan::unsort(&state).unwrap();
+        assert_eq!(deleted(&plan), [plugin.as_path()]);
+        assert_eq!(
+            removed_dirs(&plan),
+            [linear.as_path(), eq.as_path(), mixing.as_path()]
+        );
+
+        plan.execute(&mut state).unwrap();
+        assert!(!mixing.exists());
+        assert!(state.is_empty());
+
+        // A folder that still contains an unrecorded file is kept, along with its parents
+        create_file(&plugin, "Pro-Q 3");
+        create_file(&eq.join("Mine.fst"), "mine");
+        for folder in [&mixing, &eq, &linear] {
+            state.add_folder(folder);
+        }
+        record_file(&mut state, &plugin);
+
+        plan = Plan::unsort(&state).unwrap();
+        assert_eq!(removed_dirs(&plan), [linear.as_path()]);
+    }
+
+    #[test]
+    fn mirror_keeps_folders_of_groups_without_placed_files() {
+        let dir = TempDir::new().unwrap();
+        let plugin_database = create_database(dir.path(), &["FabFilter Pro-C 2"]);
+
+        let mut eq = PluginGroup::new("EQ", vec!["FabFilter Pro-Q 3".to_owned()]);
+        eq.path = Some("Mixing/EQ".to_owned());
+        let group_data = effect_groups(vec![
+            eq,
+            PluginGroup::new("Dynamics", vec!["FabFilter Pro-C 2".to_owned()]),
+        ]);
+        let eq_dir = dir.path().join("Effects/Mixing/EQ");
+
+        // The folder of a group whose plugins aren't installed must survive every run
+        let mut state = SortState::default();
+        for _ in 0..2 {
+            let (plan, folders) = Plan::sort(
+                &plugin_database,
+                &group_data,
+                OPTIONS,
+                &TypeSettings::default(),
+                &state,
+            )
+            .unwrap();
+            plan.execute(&mut state).unwrap();
+
+            let sort_actions = plan.actions.iter();
+            let mirror = Plan::mirror(&state, &folders, sort_actions).unwrap();
+            assert!(mirror.actions.is_empty(), "{:?}", mirror.actions);
+            mirror.execute(&mut state).unwrap();
+            assert!(eq_dir.is_dir());
+        }
+    }
+
+    /// Places `source` at `path` and records it in a `SortedFile`
+    fn place_file(source: &Path, path: &Path, placement: Placement) -> SortedFile {
+        placement.place(source, path).unwrap();
+        SortedFile::new(path, source, placement).unwrap()
+    }
+
+    // Creating symbolic links on Windows requires extra privileges
+    #[cfg(unix)]
+    #[test]
+    fn repointed_symlink_is_changed() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("Pro-Q 3.fst");
+        let other = dir.path().join("Pro-C 2.fst");
+        let path = dir.path().join("Sorted.fst");
+        create_file(&source, "Pro-Q 3");
+        create_file(&other, "Pro-C 2");
+
+        let file = place_file(&source, &path, Placement::Symlink);
+        assert!(file.is_unchanged().unwrap());
+
+        Placement::Symlink.place(&other, &path).unwrap();
+        assert!(!file.is_unchanged().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dangling_symlink_to_source_is_unchanged() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("Pro-Q 3.fst");
+        let path = dir.path().join("Sorted.fst");
+        create_file(&source, "Pro-Q 3");
+
+        let file = place_file(&source, &path, Placement::Symlink);
+        fs::remove_file(&source).unwrap();
+        assert!(file.exists());
+        assert!(file.is_unchanged().unwrap());
+    }
+
+    #[test]
+    fn hardlink_with_rewritten_source_is_unchanged() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("Pro-Q 3.fst");
+        let path = dir.path().join("Sorted.fst");
+        create_file(&source, "Pro-Q 3");
+
+        // Rewriting the source in place also changes the linked file
+        let file = place_file(&source, &path, Placement::Hardlink);
+        fs::write(&source, "Pro-Q 3, rescanned").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Pro-Q 3, rescanned");
+        assert!(file.is_unchanged().unwrap());
+    }
+
+    #[test]
+    fn hardlink_replaced_by_edited_file_is_changed() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("Pro-Q 3.fst");
+        let path = dir.path().join("Sorted.fst");
+        create_file(&source, "Pro-Q 3");
+
+        let file = place_file(&source, &path, Placement::Hardlink);
+        fs::remove_file(&path).unwrap();
+        fs::write(&path, "edited").unwrap();
+        assert!(!file.is_unchanged().unwrap());
+    }
+
+    #[test]
+    fn exclusive_sort_places_plugins_in_one_group() {
+        let dir = TempDir::new().unwrap();
+        let plugin_database = create_database(dir.path(), &["Pro-C 2", "Pro-Q 3"]);
+
+        let mut dynamics = PluginGroup::new("Dynamics", vec!["Pro-C 2".to_owned()]);
+        dynamics.priority = Some(1);
+        let group_data = effect_groups(vec![
+            PluginGroup::new("EQ", vec!["Pro-Q 3".to_owned(), "Pro-C 2".to_owned()]),
+            dynamics,
+            PluginGroup::new("Favourites", vec!["Pro-Q 3".to_owned()]),
+        ]);
+        let settings = TypeSettings {
+            exclusive: true,
+            ..Default::default()
+        };
+
+        let (plan, _) = Plan::sort(
+            &plugin_database,
+            &group_data,
+            OPTIONS,
+            &settings,
+            &SortState::default(),
+        )
+        .unwrap();
+
+        // The highest priority wins, and equal priorities go to the first group
+        let conflicts: Vec<(&str, &str, &str)> = plan
+            .skipped
+            .iter()
+            .filter_map(|skipped| match skipped {
+                Skipped::Conflict {
+                    group,
+                    plugin,
+                    placed_in,
+                } => Some((group.as_str(), plugin.as_str(), placed_in.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            conflicts,
+            [
+                ("EQ", "Pro-C 2", "Dynamics"),
+                ("Favourites", "Pro-Q 3", "EQ")
+            ]
+        );
+        assert_eq!(conflicts.len(), plan.skipped.len());
+
+        let effects = dir.path().join("Effects");
+        let placed: Vec<&Path> = plan
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Place { to, .. } => Some(to.as_path()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            placed,
+            [
+                effects.join("EQ/Pro-Q 3.fst"),
+                effects.join("Dynamics/Pro-C 2.fst")
+            ]
+        );
+
+        // Favourites has no plugins of its own, so it gets no folder
+        let created: Vec<&Path> = plan
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::CreateDir { path } => Some(path.as_path()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(created, [effects.join("EQ"), effects.join("Dynamics")]);
+    }
+}
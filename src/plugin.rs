@@ -1,4 +1,11 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
+
+use color_eyre::Result;
+
+use crate::{
+    config::{PluginDatabase, PluginGroupType},
+    fst::FstFile,
+};
 
 /// Determines if a path could be a VST file
 pub fn is_path_vst(path: &Path) -> bool {
@@ -8,3 +15,90 @@ pub fn is_path_vst(path: &Path) -> bool {
 
     false
 }
+
+/// An installed plugin in the plugin database, along with the
+/// `PluginGroupType` it was installed as
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedPlugin {
+    /// The name of the plugin, which is the file stem of its `.fst` file
+    pub name: String,
+    pub group_type: PluginGroupType,
+}
+
+/// Maps the plugin files referenced by the `.fst` files in the plugin
+/// database to the installed plugins that reference them.
+///
+/// This allows a VST file to be resolved to the name FL Studio uses for it,
+/// which doesn't always match its file name, e.g. `Serum_x64.dll` is installed as `Serum`.
+#[derive(Debug, Default)]
+pub struct PluginPathIndex {
+    paths: HashMap<String, Vec<IndexedPlugin>>,
+    file_names: HashMap<String, Vec<IndexedPlugin>>,
+}
+
+impl PluginPathIndex {
+    /// Creates a `PluginPathIndex` by reading the plugin path of every installed `.fst` file.
+    ///
+    /// Files that can't be parsed, or don't reference a plugin path, are ignored.
+    pub fn new(plugin_database: &PluginDatabase) -> Result<Self> {
+        let mut index = Self::default();
+        for database_group in [&plugin_database.effects, &plugin_database.generators] {
+            for folder in &database_group.installed.folders {
+                for path in folder.plugin_files()? {
+                    let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                        continue;
+                    };
+
+                    let Some(plugin_path) = FstFile::from_file(&path)
+                        .ok()
+                        .and_then(|fst| fst.plugin_path)
+                    else {
+                        continue;
+                    };
+
+                    index.insert(
+                        &plugin_path.to_string_lossy(),
+                        IndexedPlugin {
+                            name: name.to_owned(),
+                            group_type: database_group.group_type.clone(),
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(index)
+    }
+
+    fn insert(&mut self, plugin_path: &str, plugin: IndexedPlugin) {
+        let (path, file_name) = Self::normalize(plugin_path);
+        for plugins in [
+            self.paths.entry(path).or_default(),
+            self.file_names.entry(file_name).or_default(),
+        ] {
+            if !plugins.contains(&plugin) {
+                plugins.push(plugin.clone());
+            }
+        }
+    }
+
+    /// Returns every installed plugin that references the plugin file at the given [`Path`].
+    ///
+    /// Plugins are matched by their full path, ignoring case and path separators. If none
+    /// match, as the plugin database was scanned on another machine or from another
+    /// folder, they are matched by file name instead.
+    pub fn resolve(&self, plugin_path: &Path) -> &[IndexedPlugin] {
+        let (path, file_name) = Self::normalize(&plugin_path.to_string_lossy());
+        self.paths
+            .get(&path)
+            .or_else(|| self.file_names.get(&file_name))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the lowercase path, using `/` as the separator, and its file name
+    fn normalize(plugin_path: &str) -> (String, String) {
+        let path = plugin_path.replace('\\', "/").to_lowercase();
+        let file_name = path.rsplit('/').next().unwrap_or(&path).to_owned();
+        (path, file_name)
+    }
+}
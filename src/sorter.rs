@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+
+use crate::{
+    config::{Config, ConfigWarning, PluginGroupType},
+    plan::{Plan, SortOptions},
+    state::SortState,
+};
+
+/// The `Plan` used to sort the plugin groups of a single `PluginGroupType`
+#[derive(Debug)]
+pub struct TypeSortResult {
+    pub group_type: PluginGroupType,
+    pub plan: Plan,
+}
+
+/// The result of sorting plugin groups into the plugin database
+#[derive(Debug)]
+pub struct SortResult {
+    /// The `Plan` of each `PluginGroupType` that has plugin groups or an unsorted folder
+    pub types: Vec<TypeSortResult>,
+    /// The `Plan` that removes sorted files no longer defined by a plugin group,
+    /// which is empty unless mirroring
+    pub mirror: Plan,
+    /// Whether the plans were only created, and not applied
+    pub dry_run: bool,
+}
+
+impl SortResult {
+    /// Combines every `Plan` into one `Plan`
+    pub fn combined_plan(&self) -> Plan {
+        let mut combined = Plan::default();
+        for result in &self.types {
+            combined.append(result.plan.clone());
+        }
+        combined.append(self.mirror.clone());
+        combined
+    }
+}
+
+/// The result of removing sorted files from the plugin database
+#[derive(Debug)]
+pub struct UnsortResult {
+    pub plan: Plan,
+    /// Whether the plan was only created, and not applied
+    pub dry_run: bool,
+}
+
+/// Sorts the plugin groups of the `Config` into the plugin database.
+///
+/// Everything created is recorded in the `SortState` of the `Config`, even if
+/// sorting fails part way through. If `dry_run` is true, the plugin database
+/// and `SortState` are left untouched.
+///
+/// # Errors
+/// The function will return an error if there are no plugin groups, if mirroring
+/// while any plugin group file failed to load, or if any change to the plugin database fails.
+pub fn sort(config: &Config, options: SortOptions, dry_run: bool) -> Result<SortResult> {
+    // A type without plugin groups is still sorted if it has an unsorted folder
+    let type_data: Vec<_> = [&config.effects, &config.generators]
+        .into_iter()
+        .map(|group_data| {
+            (
+                group_data,
+                config.user.type_settings(&group_data.group_type),
+            )
+        })
+        .filter(|(group_data, settings)| {
+            !group_data.groups.is_empty() || settings.unsorted.is_some()
+        })
+        .collect();
+
+    if type_data.is_empty() {
+        return Err(eyre!("there are no plugin groups to sort"));
+    }
+
+    let plugin_database = config.plugin_database()?;
+    let mut state = SortState::from_file(&config.state_path)?;
+    let mut types = Vec::new();
+    let mut folders = HashSet::new();
+    for (group_data, settings) in type_data {
+        let (plan, group_folders) =
+            Plan::sort(plugin_database, group_data, options, settings, &state).wrap_err_with(
+                || eyre!("failed to plan {} plugins", group_data.group_type.name()),
+            )?;
+        folders.extend(group_folders);
+        types.push(TypeSortResult {
+            group_type: group_data.group_type.clone(),
+            plan,
+        });
+    }
+
+    // Skipped plugin groups would otherwise have their sorted files removed
+    let has_invalid_groups = config
+        .warnings
+        .iter()
+        .any(|warning| matches!(warning, ConfigWarning::InvalidGroup(_)));
+    if options.mirror && has_invalid_groups {
+        return Err(eyre!(
+            "can't mirror while plugin group files are failing to load"
+        ));
+    }
+
+    let mirror = if options.mirror {
+        let sort_actions = types.iter().flat_map(|result| &result.plan.actions);
+        Plan::mirror(&state, &folders, sort_actions).wrap_err("failed to plan mirror")?
+    } else {
+        Plan::default()
+    };
+
+    let result = SortResult {
+        types,
+        mirror,
+        dry_run,
+    };
+
+    if dry_run {
+        return Ok(result);
+    }
+
+    let executed = result
+        .types
+        .iter()
+        .map(|result| &result.plan)
+        .chain([&result.mirror])
+        .try_for_each(|plan| plan.execute(&mut state));
+    state
+        .save(&config.state_path)
+        .wrap_err("failed to save sort state")?;
+    executed?;
+
+    Ok(result)
+}
+
+/// Removes every file and folder recorded in the `SortState` of the `Config`
+/// from the plugin database.
+///
+/// If `dry_run` is true, the plugin database and `SortState` are left untouched.
+pub fn unsort(config: &Config, dry_run: bool) -> Result<UnsortResult> {
+    let mut state = SortState::from_file(&config.state_path)?;
+    let plan = Plan::unsort(&state).wrap_err("failed to plan unsort")?;
+    if dry_run {
+        return Ok(UnsortResult { plan, dry_run });
+    }
+
+    let executed = plan.execute(&mut state);
+    state
+        .save(&config.state_path)
+        .wrap_err("failed to save sort state")?;
+    executed?;
+
+    Ok(UnsortResult { plan, dry_run })
+}
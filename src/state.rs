@@ -0,0 +1,142 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::Placement;
+
+/// A file created in the plugin database when sorting,
+/// along with a hash of its contents at the time it was created
+/// and the installed plugin file it was placed from.
+///
+/// The `source` of copies sorted before it was recorded is [`None`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortedFile {
+    pub path: PathBuf,
+    pub hash: String,
+    #[serde(default)]
+    pub placement: Placement,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PathBuf>,
+}
+
+impl SortedFile {
+    /// Creates a `SortedFile` for a file that was placed at `path` from `source`
+    pub fn new(path: &Path, source: &Path, placement: Placement) -> Result<Self> {
+        Ok(Self {
+            path: path.to_owned(),
+            hash: hash_file(path)?,
+            placement,
+            source: Some(source.to_owned()),
+        })
+    }
+
+    /// Returns whether the file exists, including links whose target no longer exists
+    pub fn exists(&self) -> bool {
+        fs::symlink_metadata(&self.path).is_ok()
+    }
+
+    /// Returns whether the file is unchanged since it was sorted.
+    ///
+    /// A symbolic link is unchanged if it still points to its source, and
+    /// a hard link is unchanged if its contents still match its source.
+    pub fn is_unchanged(&self) -> Result<bool> {
+        match self.placement {
+            Placement::Copy => Ok(hash_file(&self.path)? == self.hash),
+            Placement::Symlink => {
+                let is_symlink = fs::symlink_metadata(&self.path)?.file_type().is_symlink();
+                Ok(is_symlink && fs::read_link(&self.path).ok() == self.source)
+            }
+            Placement::Hardlink => {
+                let hash = hash_file(&self.path)?;
+                if hash == self.hash {
+                    return Ok(true);
+                }
+
+                match &self.source {
+                    Some(source) if source.is_file() => Ok(hash_file(source)? == hash),
+                    _ => Ok(false),
+                }
+            }
+        }
+    }
+}
+
+/// A manifest of every folder and file created in the plugin database
+/// when sorting, which is used to reverse a sort.
+///
+/// The manifest is independent of the plugin group definitions, so
+/// groups can be changed or deleted without orphaning sorted files.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SortState {
+    #[serde(default)]
+    pub folders: Vec<PathBuf>,
+    #[serde(default)]
+    pub files: Vec<SortedFile>,
+}
+
+impl SortState {
+    /// Creates a `SortState` from a [`Path`] pointing to a TOML file.
+    ///
+    /// If the file does not exist, an empty `SortState` is returned.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path).wrap_err("failed to read state.toml")?;
+        toml::from_str(&contents).wrap_err("failed to parse state.toml")
+    }
+
+    /// Writes the `SortState` to the given [`Path`].
+    ///
+    /// If nothing is recorded, the file is removed instead.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if self.is_empty() {
+            if path.exists() {
+                fs::remove_file(path).wrap_err("failed to remove state.toml")?;
+            }
+            return Ok(());
+        }
+
+        let contents = toml::to_string(self).wrap_err("failed to serialize sort state")?;
+        fs::write(path, contents).wrap_err("failed to write state.toml")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.folders.is_empty() && self.files.is_empty()
+    }
+
+    /// Records a folder created when sorting
+    pub fn add_folder(&mut self, path: &Path) {
+        if !self.folders.iter().any(|folder| folder == path) {
+            self.folders.push(path.to_owned());
+        }
+    }
+
+    /// Removes any recorded files and folders that no longer exist
+    pub fn prune(&mut self) {
+        self.files.retain(SortedFile::exists);
+        self.folders.retain(|folder| folder.is_dir());
+    }
+
+    /// Records a file created when sorting, replacing any previous record of the same file
+    pub fn add_file(&mut self, file: SortedFile) {
+        self.files.retain(|recorded| recorded.path != file.path);
+        self.files.push(file);
+    }
+}
+
+/// Returns the hex encoded SHA-256 hash of the file at the given [`Path`]
+pub fn hash_file(path: &Path) -> Result<String> {
+    let contents =
+        fs::read(path).wrap_err_with(|| eyre!("failed to read {} to hash it", path.display()))?;
+    Ok(format!("{:x}", Sha256::digest(contents)))
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+use strsim::normalized_levenshtein;
+
+/// The minimum score a name needs to be suggested, between 0 and 1
+const MIN_SCORE: f64 = 0.6;
+
+/// Returns up to `limit` names from `candidates` that are similar to `name`,
+/// with the most similar name first.
+///
+/// Names are compared by their edit distance, ignoring case, and by the words they
+/// have in common, so `Serum` is similar to both `Serun` and `Serum x64`.
+pub fn suggest<'a>(name: &str, candidates: &'a [String], limit: usize) -> Vec<&'a str> {
+    let lowercase_name = name.to_lowercase();
+    let words = words(&lowercase_name);
+
+    let mut scored: Vec<(f64, f64, &str)> = candidates
+        .iter()
+        .filter(|candidate| candidate.as_str() != name)
+        .map(|candidate| {
+            let lowercase_candidate = candidate.to_lowercase();
+            let distance = normalized_levenshtein(&lowercase_name, &lowercase_candidate);
+            let shared = shared_words(&words, &self::words(&lowercase_candidate));
+
+            // Sharing every word is a strong match, but less so than an exact match
+            (distance.max(shared * 0.9), distance, candidate.as_str())
+        })
+        .filter(|(score, ..)| *score >= MIN_SCORE)
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.total_cmp(&a.1)));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(.., candidate)| candidate)
+        .collect()
+}
+
+/// Splits a name into its words, ignoring any punctuation
+fn words(name: &str) -> HashSet<&str> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Returns the number of shared words, relative to the name with the fewest words
+fn shared_words(a: &HashSet<&str>, b: &HashSet<&str>) -> f64 {
+    let fewest = a.len().min(b.len());
+    if fewest == 0 {
+        return 0.0;
+    }
+
+    a.intersection(b).count() as f64 / fewest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn suggests_typos_and_suffixes() {
+        let installed = candidates(&["Serum", "Surge XT", "FabFilter Pro-Q 3", "Waves CLA-76"]);
+        assert_eq!(suggest("Serun", &installed, 3), ["Serum"]);
+        assert_eq!(suggest("Serum x64", &installed, 3), ["Serum"]);
+        assert_eq!(
+            suggest("fabfilter pro-q 3", &installed, 3),
+            ["FabFilter Pro-Q 3"]
+        );
+    }
+
+    #[test]
+    fn orders_by_similarity_and_respects_limit() {
+        let installed = candidates(&["Pro-Q", "Pro-Q 2", "Pro-Q 3"]);
+        // `Pro-Q` shares every one of its words, so it is more similar than `Pro-Q 2`
+        assert_eq!(suggest("Pro-Q 3 x64", &installed, 2), ["Pro-Q 3", "Pro-Q"]);
+        assert!(suggest("Pro-Q 3 x64", &installed, 0).is_empty());
+    }
+
+    #[test]
+    fn ignores_exact_and_dissimilar_names() {
+        let installed = candidates(&["Serum", "Kontakt"]);
+        assert!(suggest("Serum", &installed, 3).is_empty());
+        assert!(suggest("Diva", &installed, 3).is_empty());
+        assert!(suggest("Diva", &[], 3).is_empty());
+    }
+}
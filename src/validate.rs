@@ -0,0 +1,146 @@
+use color_eyre::{eyre::eyre, Result};
+
+/// The maximum number of characters in a plugin group or plugin name
+pub const MAX_NAME_LENGTH: usize = 128;
+
+/// Characters that are reserved by Windows, and therefore FL Studio, in file names
+const RESERVED_CHARACTERS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Names that are reserved by Windows, with or without an extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Ensures a plugin group or plugin name can be used as a single
+/// file or folder name inside the plugin database.
+///
+/// # Errors
+/// The function will return an error if the name is empty, too long, contains
+/// path separators, `..`, reserved or control characters, ends with a dot or
+/// space, or is a name reserved by Windows.
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(eyre!("name is empty"));
+    }
+
+    let length = name.chars().count();
+    if length > MAX_NAME_LENGTH {
+        return Err(eyre!(
+            "'{name}' is {length} characters long, the limit is {MAX_NAME_LENGTH}"
+        ));
+    }
+
+    if name.contains("..") {
+        return Err(eyre!("'{name}' contains '..'"));
+    }
+
+    if let Some(c) = name
+        .chars()
+        .find(|c| RESERVED_CHARACTERS.contains(c) || c.is_control())
+    {
+        return Err(eyre!("'{name}' contains the reserved character {c:?}"));
+    }
+
+    if name.ends_with('.') || name.ends_with(' ') {
+        return Err(eyre!("'{name}' ends with a dot or space"));
+    }
+
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+    {
+        return Err(eyre!("'{name}' is a name reserved by Windows"));
+    }
+
+    Ok(())
+}
+
+/// Replaces the characters of a name that can't be used in a file or folder name with
+/// `_`, and removes any trailing dots or spaces, e.g. `Vendor: Inc.` becomes `Vendor_ Inc`.
+///
+/// The result can still be invalid, e.g. if it is a name reserved by Windows.
+pub fn sanitize_name(name: &str) -> String {
+    let sanitized: String = name
+        .replace("..", "_")
+        .chars()
+        .map(|c| {
+            if RESERVED_CHARACTERS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .take(MAX_NAME_LENGTH)
+        .collect();
+
+    sanitized.trim_end_matches(['.', ' ']).to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_names() {
+        let longest = "a".repeat(MAX_NAME_LENGTH);
+        for name in [
+            "FabFilter Pro-Q 3",
+            "Serum (VST3)",
+            "Vendor.Plugin v1.2",
+            "Console",
+            "COM10",
+            ".hidden",
+            longest.as_str(),
+        ] {
+            assert!(validate_name(name).is_ok(), "'{name}' should be valid");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        let too_long = "a".repeat(MAX_NAME_LENGTH + 1);
+        for name in [
+            "",
+            "   ",
+            ".",
+            "..",
+            "../Installed",
+            "Mixing..EQ",
+            "Mixing/EQ",
+            "Mixing\\EQ",
+            "/etc/passwd",
+            "C:\\Windows",
+            "CON",
+            "con",
+            "CON.txt",
+            "lpt1.fst",
+            "Name.",
+            "Name ",
+            "Tab\tName",
+            "What?",
+            too_long.as_str(),
+        ] {
+            assert!(validate_name(name).is_err(), "'{name}' should be invalid");
+        }
+    }
+
+    #[test]
+    fn sanitized_names_are_valid() {
+        for (name, sanitized) in [
+            ("Vendor: Inc.", "Vendor_ Inc"),
+            ("AC/DC", "AC_DC"),
+            ("../Installed", "__Installed"),
+            ("Name . ", "Name"),
+        ] {
+            assert_eq!(sanitize_name(name), sanitized);
+            assert!(validate_name(&sanitize_name(name)).is_ok());
+        }
+
+        assert_eq!(
+            sanitize_name(&"a".repeat(MAX_NAME_LENGTH * 2)).len(),
+            MAX_NAME_LENGTH
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Groups plugin names by their vendor, given as `(vendor, plugin)` pairs.
+///
+/// Vendor names that only differ in case, whitespace or punctuation are merged, e.g.
+/// `FabFilter` and `Fabfilter`. Each `(alias, vendor)` pair in `aliases` also merges
+/// `alias` into `vendor`. Each group is named after the most common spelling of its vendor,
+/// or the alphabetically first of the most common spellings if several are equally common.
+pub fn group_by_vendor<'a>(
+    plugins: impl IntoIterator<Item = (&'a str, &'a str)>,
+    aliases: &[(String, String)],
+) -> BTreeMap<String, BTreeSet<String>> {
+    let aliases: HashMap<String, &str> = aliases
+        .iter()
+        .map(|(alias, vendor)| (normalize(alias), vendor.as_str()))
+        .collect();
+
+    let mut vendors: HashMap<String, (HashMap<&str, usize>, BTreeSet<String>)> = HashMap::new();
+    for (vendor, plugin) in plugins {
+        let vendor = aliases.get(&normalize(vendor)).copied().unwrap_or(vendor);
+        let (spellings, plugins) = vendors.entry(normalize(vendor)).or_default();
+        *spellings.entry(vendor.trim()).or_default() += 1;
+        plugins.insert(plugin.to_owned());
+    }
+
+    vendors
+        .into_values()
+        .filter_map(|(spellings, plugins)| {
+            let (name, _) = spellings
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))?;
+            Some((name.to_owned(), plugins))
+        })
+        .collect()
+}
+
+/// Returns the lowercase alphanumeric characters of a vendor name
+fn normalize(vendor: &str) -> String {
+    vendor
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(plugins: &[&str]) -> BTreeSet<String> {
+        plugins.iter().map(|plugin| plugin.to_string()).collect()
+    }
+
+    #[test]
+    fn merges_spellings_of_the_same_vendor() {
+        let groups = group_by_vendor(
+            [
+                ("FabFilter", "Pro-Q 3"),
+                ("Fabfilter", "Pro-C 2"),
+                ("FabFilter", "Pro-L 2"),
+                ("Xfer Records", "Serum"),
+                ("xfer-records ", "OTT"),
+            ],
+            &[],
+        );
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups["FabFilter"],
+            names(&["Pro-C 2", "Pro-L 2", "Pro-Q 3"])
+        );
+        assert_eq!(groups["Xfer Records"], names(&["OTT", "Serum"]));
+    }
+
+    #[test]
+    fn merges_aliases_into_their_vendor() {
+        let aliases = [("Xfer".to_owned(), "Xfer Records".to_owned())];
+        let groups = group_by_vendor([("Xfer", "OTT"), ("Xfer Records", "Serum")], &aliases);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups["Xfer Records"], names(&["OTT", "Serum"]));
+    }
+
+    #[test]
+    fn uses_the_alphabetically_first_spelling_when_tied() {
+        // The order the spellings are seen in doesn't matter
+        for plugins in [
+            [("Fabfilter", "Pro-C 2"), ("FabFilter", "Pro-Q 3")],
+            [("FabFilter", "Pro-Q 3"), ("Fabfilter", "Pro-C 2")],
+        ] {
+            let groups = group_by_vendor(plugins, &[]);
+            assert_eq!(groups.keys().collect::<Vec<_>>(), ["FabFilter"]);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fs, path::Path};
+
+use tempfile::TempDir;
+
+use fl_plugin_sorter::{
+    config::{Config, Placement},
+    plan::{Skipped, SortOptions},
+    sort, unsort,
+};
+
+const OPTIONS: SortOptions = SortOptions {
+    placement: Placement::Copy,
+    both: false,
+    mirror: false,
+};
+
+/// Creates a config folder with a single `EQ` effect plugin group, and a plugin
+/// database with `FabFilter Pro-Q 3` installed, returning the loaded `Config`
+fn create_config(root: &Path) -> Config {
+    let database = root.join("database");
+    for folder in ["Effects", "Generators", "Installed/Generators"] {
+        fs::create_dir_all(database.join(folder)).unwrap();
+    }
+
+    let installed = database.join("Installed/Effects/VST");
+    fs::create_dir_all(&installed).unwrap();
+    fs::copy(
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/pro_q3_vst2.fst"
+        ),
+        installed.join("FabFilter Pro-Q 3.fst"),
+    )
+    .unwrap();
+
+    let config_dir = root.join("config");
+    fs::create_dir_all(config_dir.join("effect")).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        format!("plugin_database_path = '{}'\n", database.display()),
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("effect/eq.toml"),
+        "name = \"EQ\"\nplugins = [\"FabFilter Pro-Q 3\"]\n",
+    )
+    .unwrap();
+
+    Config::from_file(&config_dir, true).unwrap()
+}
+
+#[test]
+fn sort_leaves_unmanaged_files_alone() {
+    let root = TempDir::new().unwrap();
+    let config = create_config(root.path());
+
+    let user_file = root
+        .path()
+        .join("database/Effects/EQ/FabFilter Pro-Q 3.fst");
+    fs::create_dir_all(user_file.parent().unwrap()).unwrap();
+    fs::write(&user_file, "mine").unwrap();
+
+    let result = sort(&config, OPTIONS, false).unwrap();
+    let plan = result.combined_plan();
+    assert_eq!(plan.placed_count(), 0);
+    assert!(matches!(
+        plan.skipped.as_slice(),
+        [Skipped::Unmanaged { path }] if path == &user_file
+    ));
+
+    unsort(&config, false).unwrap();
+    assert_eq!(fs::read_to_string(&user_file).unwrap(), "mine");
+}
+
+#[test]
+fn sort_overwrites_unchanged_sorted_files() {
+    let root = TempDir::new().unwrap();
+    let config = create_config(root.path());
+
+    sort(&config, OPTIONS, false).unwrap();
+    let plan = sort(&config, OPTIONS, false).unwrap().combined_plan();
+    assert_eq!(plan.placed_count(), 1);
+    assert!(plan.skipped.is_empty());
+
+    let plan = unsort(&config, false).unwrap().plan;
+    assert_eq!(plan.deleted_count(), 1);
+    assert!(!root.path().join("database/Effects/EQ").exists());
+}
+
+#[test]
+fn sort_adopts_files_matching_their_source() {
+    let root = TempDir::new().unwrap();
+    let config = create_config(root.path());
+
+    // A copy placed by a version that didn't record sorted files
+    let sorted_file = root
+        .path()
+        .join("database/Effects/EQ/FabFilter Pro-Q 3.fst");
+    fs::create_dir_all(sorted_file.parent().unwrap()).unwrap();
+    fs::copy(
+        root.path()
+            .join("database/Installed/Effects/VST/FabFilter Pro-Q 3.fst"),
+        &sorted_file,
+    )
+    .unwrap();
+
+    let plan = sort(&config, OPTIONS, false).unwrap().combined_plan();
+    assert_eq!(plan.placed_count(), 1);
+    assert!(plan.skipped.is_empty());
+
+    let plan = unsort(&config, false).unwrap().plan;
+    assert_eq!(plan.deleted_count(), 1);
+    assert!(!sorted_file.exists());
+}